@@ -1,9 +1,12 @@
 use delegated_proving_test_bindings::delegated_proving_test::*;
-use leo_bindings::leo_bindings_sdk::{Account, Client, Credentials, NetworkVm, VMManager};
+use leo_bindings::leo_bindings_sdk::{
+    Account, Client, Credentials, LocalProver, NetworkVm, VMManager,
+};
 use leo_bindings::utils::init_test_logger;
 use snarkvm::prelude::*;
 
 const ENDPOINT: &str = "https://api.explorer.provable.com";
+const DEVNET_ENDPOINT: &str = "http://localhost:3030";
 
 const TEST_A: u64 = 1000;
 const TEST_B: u64 = 10;
@@ -30,6 +33,23 @@ fn test_network_delegated_proving() {
     run_delegated_tests(vm_manager, &alice);
 }
 
+#[test]
+fn test_devnet_local_prover() {
+    init_test_logger();
+    let alice: Account<TestnetV0> = Account::dev_account(0).unwrap();
+    let node = Client::new(DEVNET_ENDPOINT, None).unwrap();
+    let prover = LocalProver::new(NetworkVm::new(&node).unwrap())
+        .with_fee_payer(alice.clone())
+        .spawn()
+        .unwrap();
+    let client = Client::new(DEVNET_ENDPOINT, Some(prover.credentials()))
+        .unwrap()
+        .with_prover_url(&prover.prover_url())
+        .with_prover_broadcast(true);
+    let vm_manager = NetworkVm::new(&client).unwrap();
+    run_delegated_tests(vm_manager, &alice);
+}
+
 fn run_delegated_tests<V: VMManager<TestnetV0>>(vm: V, alice: &Account<TestnetV0>) {
     let program = DelegatedProvingTestAleo::new(alice, vm).unwrap();
     let result = program
@@ -25,14 +25,22 @@ fn test_dev_sim() {
 
 fn run_dev_tests<V: VMManager<TestnetV0>>(vm: V, alice: &Account<TestnetV0>) {
     let dev = DevAleo::new(alice, vm).unwrap();
-    let user = dev.create_user(alice, alice.address(), 0, 0).unwrap();
+    let user = dev
+        .create_user(alice, alice.address(), 0, 0)
+        .unwrap()
+        .owned()
+        .unwrap();
     dbg!(&user);
     let balance = dev.consume_user(alice, user).unwrap();
     dbg!(balance);
 
     let a = A::new(1);
     let b = B::new(2, a);
-    let container = dev.create_container(alice, alice.address(), b).unwrap();
+    let container = dev
+        .create_container(alice, alice.address(), b)
+        .unwrap()
+        .owned()
+        .unwrap();
     dbg!(&container);
     let extracted_b = dev.consume_container(alice, container).unwrap();
     dbg!(extracted_b);
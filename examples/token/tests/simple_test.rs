@@ -1,6 +1,11 @@
 use credits_bindings::credits::*;
-use leo_bindings::leo_bindings_sdk::{Account, Client, LocalVM, NetworkVm, VMManager};
-use snarkvm::prelude::TestnetV0;
+use leo_bindings::leo_bindings_sdk::proptest::strategy::Strategy;
+use leo_bindings::leo_bindings_sdk::{
+    Account, AuthorizationBundle, BudgetLayer, Client, CostReport, Error, FileCheckpointStore,
+    Indexer, LocalNode, LocalVM, NetworkVm, PendingTransaction, RecordOutput, RecordSource,
+    Recording, Replayer, RetryLayer, Stack, ToValue, TraceLayer, VMManager, block_on, cost_report,
+};
+use snarkvm::prelude::{TestnetV0, Value};
 use token_bindings::token::*;
 
 const ENDPOINT: &str = "http://localhost:3030";
@@ -14,6 +19,102 @@ fn token_net() {
     run_token_tests(vm_manager, &alice);
 }
 
+#[test]
+fn token_local_node() {
+    leo_bindings::utils::init_test_logger();
+    let alice: Account<TestnetV0> = Account::dev_account(0).unwrap();
+    let node = LocalNode::spawn(LocalVM::new().unwrap()).unwrap();
+    let client = Client::new(&node.endpoint(), None).unwrap();
+    let vm_manager = NetworkVm::new(&client).unwrap();
+    run_token_tests(vm_manager, &alice);
+}
+
+#[test]
+fn token_local_node_batch() {
+    leo_bindings::utils::init_test_logger();
+    let alice: Account<TestnetV0> = Account::dev_account(0).unwrap();
+    let bob = Account::new(&mut rand::rng()).unwrap();
+    let node = LocalNode::spawn(LocalVM::new().unwrap()).unwrap();
+    let client = Client::new(&node.endpoint(), None).unwrap();
+    let vm = NetworkVm::new(&client).unwrap();
+    let token = TokenAleo::new(&alice, vm.clone()).unwrap();
+
+    let bundles: Vec<_> = (0..3)
+        .map(|_| {
+            token
+                .authorize_mint_public(&alice, bob.address(), 10)
+                .unwrap()
+        })
+        .collect();
+    let pending: Vec<PendingTransaction<TestnetV0>> = vm
+        .submit_batch(&bundles)
+        .into_iter()
+        .map(|pending| pending.unwrap())
+        .collect();
+    for result in PendingTransaction::wait_all(pending) {
+        result.unwrap();
+    }
+    assert_eq!(token.get_account(bob.address()), Some(30));
+}
+
+#[test]
+fn token_local_node_indexer() {
+    leo_bindings::utils::init_test_logger();
+    let alice: Account<TestnetV0> = Account::dev_account(0).unwrap();
+    let bob = Account::new(&mut rand::rng()).unwrap();
+    let node = LocalNode::spawn(LocalVM::new().unwrap()).unwrap();
+    let client = Client::new(&node.endpoint(), None).unwrap();
+    let token = TokenAleo::new(&alice, NetworkVm::new(&client).unwrap()).unwrap();
+    token.mint_public(&alice, alice.address(), 100).unwrap();
+    token.transfer_public(&alice, bob.address(), 30).unwrap();
+
+    let path = std::env::temp_dir().join(format!("token_index_{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let index = |client: &Client| {
+        Indexer::new(
+            client.clone(),
+            token.program_id,
+            FileCheckpointStore::new(&path),
+            0,
+        )
+        .unwrap()
+        .confirmations(0)
+    };
+
+    let mut indexer = index(&client);
+    let indexed = block_on(indexer.poll()).unwrap();
+    let calls: Vec<TokenCall<TestnetV0>> =
+        indexed.iter().map(|call| call.decode().unwrap()).collect();
+    assert_eq!(calls.len(), 2);
+    assert!(matches!(
+        &calls[0],
+        TokenCall::MintPublic { arg1, arg2: 100 } if *arg1 == alice.address()
+    ));
+    assert!(matches!(
+        &calls[1],
+        TokenCall::TransferPublic { arg1, arg2: 30 } if *arg1 == bob.address()
+    ));
+    indexer.commit().unwrap();
+
+    let transaction =
+        block_on(client.transaction::<TestnetV0>(&indexed[1].transaction_id)).unwrap();
+    let transition = transaction
+        .transitions()
+        .find(|transition| transition.id() == &indexed[1].transition_id)
+        .unwrap();
+    let decoded = TokenCall::decode(transition).unwrap();
+    assert_eq!(decoded.encode(), calls[1].encode());
+
+    // A new indexer resumes after the checkpoint.
+    token.mint_public(&alice, bob.address(), 5).unwrap();
+    let mut indexer = index(&client);
+    let calls = block_on(indexer.poll()).unwrap();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].function.to_string(), "mint_public");
+    assert_eq!(calls[0].finalize.len(), 2);
+    std::fs::remove_file(&path).unwrap();
+}
+
 #[test]
 fn token_sim() {
     leo_bindings::utils::init_test_logger();
@@ -22,6 +123,455 @@ fn token_sim() {
     run_token_tests(sim_vm, &alice);
 }
 
+#[test]
+fn token_call_sim() {
+    let (alice, _, token) = local_token();
+    let bob = Account::new(&mut rand::rng()).unwrap();
+
+    let queue = vec![
+        TokenCall::MintPublic {
+            arg1: alice.address(),
+            arg2: 10,
+        },
+        TokenCall::TransferPublic {
+            arg1: bob.address(),
+            arg2: 4,
+        },
+    ];
+    assert!(format!("{}", queue[0]).starts_with("token.aleo/mint_public("));
+    for call in &queue {
+        token.call(&alice, call).unwrap();
+    }
+    assert_eq!(token.get_account(alice.address()), Some(6));
+    assert_eq!(token.get_account(bob.address()), Some(4));
+}
+
+#[test]
+fn token_transcript_sim() {
+    leo_bindings::utils::init_test_logger();
+    let alice: Account<TestnetV0> = Account::dev_account(0).unwrap();
+    let bob = Account::new(&mut rand::rng()).unwrap();
+    let path = std::env::temp_dir().join(format!("token_transcript_{}.jsonl", std::process::id()));
+
+    let vm = Recording::create(LocalVM::new().unwrap(), &path)
+        .unwrap()
+        .label("alice", &alice)
+        .label("bob", &bob);
+    let token = TokenAleo::new(&alice, vm).unwrap();
+    let rec = token.mint_private(&alice, bob.address(), 100).unwrap();
+    let rec = rec.decrypt(&bob).unwrap();
+    token
+        .transfer_private(&bob, rec, alice.address(), 10)
+        .unwrap();
+    token.mint_public(&alice, bob.address(), 5).unwrap();
+
+    let replayer = Replayer::new(LocalVM::new().unwrap())
+        .label("alice", &alice)
+        .label("bob", &bob);
+    let report = replayer.replay(&path).unwrap();
+    assert!(report.is_ok(), "{report}");
+    assert_eq!(report.entries, 4);
+
+    // A changed output is reported as a divergence.
+    let transcript = std::fs::read_to_string(&path).unwrap();
+    std::fs::write(&path, transcript.replace("90u64", "91u64")).unwrap();
+    let report = Replayer::new(LocalVM::new().unwrap())
+        .label("alice", &alice)
+        .label("bob", &bob)
+        .replay(&path)
+        .unwrap();
+    assert_eq!(report.divergences.len(), 1, "{report}");
+    assert_eq!(report.divergences[0].call, "token.aleo/transfer_private");
+
+    // A program already deployed with other bytecode is reported as a divergence.
+    let (deploy, rest) = transcript.split_once('\n').unwrap();
+    let deploy = deploy.replace("mint_public", "mint_publik");
+    std::fs::write(&path, format!("{deploy}\n{rest}")).unwrap();
+    let deployed = LocalVM::new().unwrap();
+    TokenAleo::new(&alice, deployed.clone()).unwrap();
+    let report = Replayer::new(deployed)
+        .label("alice", &alice)
+        .label("bob", &bob)
+        .replay(&path)
+        .unwrap();
+    assert_eq!(report.divergences.len(), 1, "{report}");
+    assert_eq!(report.divergences[0].call, "deploy token.aleo");
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn token_layers_sim() {
+    leo_bindings::utils::init_test_logger();
+    let alice: Account<TestnetV0> = Account::dev_account(0).unwrap();
+    let vm = LocalVM::new().unwrap();
+    let layers = |budget| {
+        vm.clone()
+            .layer(Stack::new(TraceLayer, RetryLayer::new(2)))
+            .layer(BudgetLayer::new(budget))
+    };
+
+    // The deployment fee is checked against the budget before deploying.
+    let budgeted = layers(1);
+    let err = TokenAleo::new(&alice, budgeted.clone()).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<Error>(),
+        Some(Error::BudgetExceeded {
+            budget: 1,
+            spent: 0,
+            ..
+        })
+    ));
+    assert_eq!(budgeted.spent(), 0);
+
+    // Deploy without a budget, then allow one mint.
+    let token = TokenAleo::new(&alice, vm.clone()).unwrap();
+    let fee = token
+        .estimate_mint_public(&alice, alice.address(), 100)
+        .unwrap()
+        .total_fee;
+    let token = TokenAleo::new(&alice, layers(fee + fee / 2)).unwrap();
+    token.mint_public(&alice, alice.address(), 100).unwrap();
+    assert_eq!(token.vm_manager.spent(), fee);
+
+    let err = token.mint_public(&alice, alice.address(), 100).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<Error>(),
+        Some(Error::BudgetExceeded { fee: f, .. }) if *f == fee
+    ));
+    assert_eq!(token.get_account(alice.address()), Some(100));
+}
+
+#[test]
+fn token_cost_report_sim() {
+    let (alice, _, token) = local_token();
+    // Other tests record into the same report concurrently.
+    let calls = |report: &CostReport| {
+        report
+            .executions
+            .get("token.aleo/mint_public")
+            .map_or(0, |costs| costs.calls)
+    };
+    let before = calls(&cost_report());
+    token.mint_public(&alice, alice.address(), 100).unwrap();
+    token.mint_public(&alice, alice.address(), 200).unwrap();
+
+    let report = cost_report();
+    assert!(calls(&report) >= before + 2);
+    let costs = &report.executions["token.aleo/mint_public"];
+    assert!(costs.min_fee > 0 && costs.min_fee <= costs.mean_fee());
+    assert!(costs.mean_fee() <= costs.max_fee);
+    let deployment = &report.deployments["token.aleo"];
+    assert!(deployment.variables > 0 && deployment.constraints > 0);
+    assert!(deployment.total_fee >= deployment.synthesis_cost + deployment.storage_cost);
+    assert!(
+        report
+            .to_markdown()
+            .contains("| `token.aleo/mint_public` |")
+    );
+}
+
+#[test]
+fn token_estimate_sim() {
+    let (alice, vm, token) = local_token();
+    let credits = CreditsAleo::new(&alice, vm).unwrap();
+
+    let balance_before = credits.get_account(alice.address()).unwrap();
+    let estimate = token
+        .estimate_mint_public(&alice, alice.address(), 100)
+        .unwrap();
+    assert_eq!(estimate.function, "mint_public");
+    assert!(estimate.execution_cost > 0);
+    assert_eq!(
+        estimate.total_fee,
+        estimate.storage_cost + estimate.execution_cost
+    );
+    assert_eq!(token.get_account(alice.address()), None);
+    assert_eq!(
+        credits.get_account(alice.address()).unwrap(),
+        balance_before
+    );
+
+    token.mint_public(&alice, alice.address(), 100).unwrap();
+    let fee = balance_before - credits.get_account(alice.address()).unwrap();
+    assert_eq!(fee, estimate.total_fee);
+}
+
+#[test]
+fn token_simulate_sim() {
+    let (alice, _, token) = local_token();
+    let bob: Account<TestnetV0> = Account::dev_account(1).unwrap();
+    token.mint_public(&alice, alice.address(), 100).unwrap();
+
+    let (_, writes) = token
+        .simulate_transfer_public(&alice, bob.address(), 30)
+        .unwrap();
+    let write = |account: &Account<TestnetV0>| {
+        writes
+            .iter()
+            .find(|write| Value::Plaintext(write.key.clone()) == account.address().to_value())
+            .unwrap()
+    };
+    assert_eq!(writes.len(), 2);
+    assert_eq!(write(&alice).old, Some(100u64.to_value()));
+    assert_eq!(write(&alice).new, Some(70u64.to_value()));
+    assert_eq!(write(&bob).old, None);
+    assert_eq!(write(&bob).new, Some(30u64.to_value()));
+
+    assert_eq!(token.get_account(alice.address()), Some(100));
+    assert_eq!(token.get_account(bob.address()), None);
+}
+
+#[test]
+fn token_receipt_sim() {
+    let (alice, vm, token) = local_token();
+    let bob: Account<TestnetV0> = Account::dev_account(1).unwrap();
+    token.mint_public(&alice, alice.address(), 100).unwrap();
+    token.transfer_public(&alice, bob.address(), 30).unwrap();
+
+    let receipt = vm.last_receipt().unwrap();
+    let writes = TokenWrite::decode_all(&receipt.writes);
+    assert_eq!(writes.len(), 2);
+    for write in writes {
+        match write {
+            TokenWrite::Account { key, old, new } if key == alice.address() => {
+                assert_eq!((old, new), (Some(100), Some(70)))
+            }
+            TokenWrite::Account { key, old, new } if key == bob.address() => {
+                assert_eq!((old, new), (None, Some(30)))
+            }
+            write => panic!("Unexpected write {write:?}"),
+        }
+    }
+}
+
+#[test]
+fn token_mapping_helpers_sim() {
+    let (alice, _, token) = local_token();
+    let bob: Account<TestnetV0> = Account::dev_account(1).unwrap();
+    assert_eq!(token.len_account(), 0);
+
+    token.mint_public(&alice, alice.address(), 100).unwrap();
+    token.set_account(bob.address(), 5);
+    let mut entries = token.entries_account();
+    entries.sort_by_key(|(_, amount)| *amount);
+    assert_eq!(entries, vec![(bob.address(), 5), (alice.address(), 100)]);
+    assert!(token.contains_account(alice.address()).unwrap());
+
+    assert!(token.remove_account(alice.address()));
+    assert!(!token.remove_account(alice.address()));
+    assert!(!token.contains_account(alice.address()).unwrap());
+    assert_eq!(token.len_account(), 1);
+}
+
+#[test]
+fn token_fallible_mapping_sim() {
+    let (alice, _, token) = local_token();
+    let bob: Account<TestnetV0> = Account::dev_account(1).unwrap();
+    assert_eq!(token.try_get_account(bob.address()).unwrap(), None);
+
+    token.try_set_account(bob.address(), 7).unwrap();
+    assert_eq!(token.try_get_account(bob.address()).unwrap(), Some(7));
+    assert_eq!(token.get_account(bob.address()), Some(7));
+
+    assert!(token.try_remove_account(bob.address()).unwrap());
+    assert!(!token.try_remove_account(bob.address()).unwrap());
+    assert_eq!(token.try_get_account(bob.address()).unwrap(), None);
+}
+
+#[test]
+fn token_state_diff_sim() {
+    let (alice, _, token) = local_token();
+    let bob: Account<TestnetV0> = Account::dev_account(1).unwrap();
+    token.mint_public(&alice, alice.address(), 100).unwrap();
+
+    let before = token.state().unwrap();
+    token.transfer_public(&alice, bob.address(), 30).unwrap();
+    let after = token.state().unwrap();
+
+    let diff = before.diff(&after);
+    assert_eq!(diff.account.added, vec![(bob.address(), 30)]);
+    assert_eq!(diff.account.changed, vec![(alice.address(), 100, 70)]);
+    assert!(diff.account.removed.is_empty());
+    assert!(diff.to_string().contains("account:"));
+
+    let fetched = token.state_for(&[bob.address(), alice.address()]).unwrap();
+    after.assert_unchanged(&fetched);
+}
+
+#[test]
+fn token_fuzz_sim() {
+    let (alice, _, token) = local_token();
+    let bob: Account<TestnetV0> = Account::dev_account(1).unwrap();
+    let actors = [alice.clone(), bob.clone()];
+
+    let holders = [alice.address(), bob.address()];
+    token
+        .fuzzer(&actors)
+        .runs(4)
+        .depth(8)
+        .invariant("only actors hold tokens", move |token| {
+            token
+                .state()
+                .unwrap()
+                .account
+                .keys()
+                .all(|holder| holders.contains(holder))
+        })
+        .run();
+
+    let failure = token
+        .fuzzer(&actors)
+        .seed(7)
+        .runs(8)
+        .depth(8)
+        .invariant("balances stay below 1000", |token| {
+            token
+                .state()
+                .unwrap()
+                .account
+                .values()
+                .all(|amount| *amount < 1000)
+        })
+        .check()
+        .expect("a large mint breaks the invariant");
+    assert_eq!(failure.steps.len(), 1);
+    assert!(matches!(
+        failure.steps[0].call,
+        TokenCall::MintPublic { .. }
+    ));
+    assert!(failure.to_string().contains("seed 7"));
+
+    let small_mints = token
+        .fuzzer(&actors)
+        .seed(7)
+        .runs(8)
+        .depth(8)
+        .strategy("mint_public", |actors| {
+            (actors.account(), 0u64..10).prop_map(|(receiver, amount)| TokenCall::MintPublic {
+                arg1: receiver.address(),
+                arg2: amount,
+            })
+        })
+        .invariant("balances stay below 1000", |token| {
+            token
+                .state()
+                .unwrap()
+                .account
+                .values()
+                .all(|amount| *amount < 1000)
+        })
+        .check();
+    assert!(small_mints.is_none());
+}
+
+#[test]
+fn token_private_fee_sim() {
+    leo_bindings::utils::init_test_logger();
+    let alice: Account<TestnetV0> = Account::dev_account(0).unwrap();
+    let vm = LocalVM::new().unwrap();
+    let credits = CreditsAleo::new(&alice, vm.clone()).unwrap();
+    credits
+        .transfer_public_to_private(&alice, alice.address(), 100_000_000)
+        .unwrap();
+
+    let vm = vm.with_private_fees();
+    let token = TokenAleo::new(&alice, vm.clone()).unwrap();
+    let balance_before = credits.get_account(alice.address()).unwrap();
+    token.mint_public(&alice, alice.address(), 100).unwrap();
+    let balance_after = credits.get_account(alice.address()).unwrap();
+    assert_eq!(balance_before, balance_after);
+    assert!(!vm.unspent_credits(&alice).unwrap().is_empty());
+
+    // The smallest record is the call's input, so the fee has to use another one.
+    let small = credits
+        .transfer_public_to_private(&alice, alice.address(), 1_000_000)
+        .unwrap()
+        .0
+        .decrypt(&alice)
+        .unwrap();
+    let private_credits = CreditsAleo::new(&alice, vm.clone()).unwrap();
+    private_credits
+        .transfer_private(&alice, small, alice.address(), 1)
+        .unwrap();
+}
+
+#[test]
+fn token_sponsored_sim() {
+    let (alice, vm, token) = local_token();
+    let bob = Account::new(&mut rand::rng()).unwrap();
+    let credits = CreditsAleo::new(&alice, vm.clone()).unwrap();
+
+    token.mint_public(&alice, bob.address(), 100).unwrap();
+    token
+        .transfer_public_sponsored(&bob, &alice, alice.address(), 40)
+        .unwrap();
+    assert_eq!(token.get_account(bob.address()), Some(60));
+    assert_eq!(credits.get_account(bob.address()), None);
+
+    // A broadcast authorization leaves a receipt like any other call.
+    let receipt = vm.last_receipt().unwrap();
+    let writes = TokenWrite::decode_all(&receipt.writes);
+    assert_eq!(writes.len(), 2, "{writes:?}");
+}
+
+#[test]
+fn token_offline_signing_sim() {
+    let (alice, _, token) = local_token();
+
+    let bundle = token
+        .authorize_mint_private(&alice, alice.address(), 5)
+        .unwrap();
+    let bundle: AuthorizationBundle<TestnetV0> = bundle.to_string().parse().unwrap();
+    assert!(token.broadcast_mint_public(&bundle).is_err());
+    let rec = token.broadcast_mint_private(&bundle).unwrap();
+    assert_eq!(*rec.owned().unwrap().amount(), 5);
+}
+
+#[test]
+fn token_fork_sim() {
+    let (alice, remote_vm, remote) = local_token();
+    let bob = Account::new(&mut rand::rng()).unwrap();
+    remote.mint_public(&alice, alice.address(), 100).unwrap();
+    let node = LocalNode::spawn(remote_vm).unwrap();
+
+    let client = Client::new(&node.endpoint(), None).unwrap();
+    let fork = LocalVM::fork(&client).unwrap();
+    assert!(fork.fork_height().is_some());
+    let token = TokenAleo::new(&alice, fork).unwrap();
+    assert_eq!(token.get_account(alice.address()), Some(100));
+
+    token.transfer_public(&alice, bob.address(), 30).unwrap();
+    assert_eq!(token.get_account(alice.address()), Some(70));
+    assert_eq!(token.get_account(bob.address()), Some(30));
+    assert_eq!(remote.get_account(alice.address()), Some(100));
+    assert_eq!(remote.get_account(bob.address()), None);
+}
+
+#[test]
+fn token_fork_pinned_sim() {
+    let (alice, remote_vm, remote) = local_token();
+    let bob = Account::new(&mut rand::rng()).unwrap();
+    remote.mint_public(&alice, alice.address(), 100).unwrap();
+    let node = LocalNode::spawn(remote_vm).unwrap();
+
+    let client = Client::new(&node.endpoint(), None).unwrap();
+    let height = LocalVM::fork(&client).unwrap().fork_height().unwrap();
+    let token = TokenAleo::new(&alice, LocalVM::fork_at(&client, height).unwrap()).unwrap();
+    assert_eq!(token.get_account(alice.address()), Some(100));
+
+    remote.mint_public(&alice, bob.address(), 5).unwrap();
+    assert!(token.try_get_account(bob.address()).is_err());
+}
+
+/// Deploys the token program on a fresh `LocalVM` as the first dev account.
+fn local_token() -> (Account<TestnetV0>, LocalVM, TokenAleo<TestnetV0, LocalVM>) {
+    leo_bindings::utils::init_test_logger();
+    let alice: Account<TestnetV0> = Account::dev_account(0).unwrap();
+    let vm = LocalVM::new().unwrap();
+    let token = TokenAleo::new(&alice, vm.clone()).unwrap();
+    (alice, vm, token)
+}
+
 fn run_token_tests<V: VMManager<TestnetV0> + Clone>(vm: V, alice: &Account<TestnetV0>) {
     let rng = &mut rand::rng();
     let bob = Account::new(rng).unwrap();
@@ -50,9 +600,16 @@ fn run_token_tests<V: VMManager<TestnetV0> + Clone>(vm: V, alice: &Account<Testn
 
     let rec = token.mint_private(alice, bob.address(), 100).unwrap();
     dbg!(&rec);
+    let rec = rec.decrypt(&bob).unwrap();
     let (rec1, rec2) = token
-        .transfer_private(&bob, rec, bob.address(), 10)
+        .transfer_private(&bob, rec, alice.address(), 10)
         .unwrap();
     dbg!(&rec1);
     dbg!(&rec2);
+    assert_eq!(*rec1.owned().unwrap().amount(), 90);
+    let RecordOutput::Encrypted(rec2) = rec2 else {
+        panic!("Expected a record encrypted for alice");
+    };
+    assert!(rec2.is_owner(alice));
+    assert_eq!(*rec2.decrypt(alice).unwrap().amount(), 10);
 }
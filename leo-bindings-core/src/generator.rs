@@ -1,7 +1,7 @@
 use crate::types::ToRustType;
 use convert_case::{Case::Pascal, Casing};
 use itertools::Itertools;
-use leo_abi_types::{Interface, Mode, Program, Record};
+use leo_abi_types::{FunctionInput, FunctionOutput, Interface, Mode, Program, Record};
 use proc_macro2::{Ident, Literal, Span, TokenStream};
 use quote::quote;
 
@@ -36,8 +36,30 @@ pub fn generate_program_module(abi: &Program, imports: &[ImportRef]) -> TokenStr
     let view_types = generate_function_types(&abi.views);
     let mapping_types = generate_mapping_types(&abi.mappings);
 
+    let call_enum_name = Ident::new(&format!("{program_id_pascal}Call"), Span::call_site());
+    let call_enum = generate_call_enum(
+        &function_types,
+        &call_enum_name,
+        &Literal::string(abi.program.as_str()),
+    );
+
+    let write_enum = generate_write_enum(
+        &mapping_types,
+        &Ident::new(&format!("{program_id_pascal}Write"), Span::call_site()),
+        &Literal::string(abi.program.as_str()),
+    );
+
+    let state_name = Ident::new(&format!("{program_id_pascal}State"), Span::call_site());
+    let state = generate_state(
+        &mapping_types,
+        &state_name,
+        &Ident::new(&format!("{program_id_pascal}StateDiff"), Span::call_site()),
+    );
+
     let program_impl = generate_program_impl(
         imports,
+        &call_enum_name,
+        &state_name,
         &function_types,
         &view_types,
         &mapping_types,
@@ -52,7 +74,7 @@ pub fn generate_program_module(abi: &Program, imports: &[ImportRef]) -> TokenStr
         // It is not intended for manual editing.
         pub mod #program_module {
             #[allow(unused_imports)]
-            use leo_bindings_sdk::{Account, Address, FromValue, LocalVM, ToValue, VMManager, anyhow, log, snarkvm, indexmap::IndexMap};
+            use leo_bindings_sdk::{Account, Address, Actors, Arbitrary, AuthorizationBundle, CallOutput, DecodeCall, ExecutionStats, FromValue, FuzzCall, Fuzzer, LocalVM, MappingDiff, MappingWrite, ProgramCall, ProgramRecord, RecordOutput, ToValue, VMManager, anyhow, log, snarkvm, indexmap::IndexMap, proptest::strategy::{BoxedStrategy, Just, Strategy}};
             use anyhow::{anyhow, Result};
             use snarkvm::prelude::*;
 
@@ -63,12 +85,20 @@ pub fn generate_program_module(abi: &Program, imports: &[ImportRef]) -> TokenStr
             #(#records)*
 
             #program_impl
+
+            #call_enum
+
+            #write_enum
+
+            #state
         }
     }
 }
 
 fn generate_program_impl(
     imports: &[ImportRef],
+    call_enum: &Ident,
+    state_name: &Ident,
     function_types: &[FunctionTypes],
     view_types: &[FunctionTypes],
     mapping_types: &[MappingTypes],
@@ -107,8 +137,45 @@ fn generate_program_impl(
     let mapping_setter_implementations: Vec<TokenStream> =
         mapping_types.iter().map(generate_mapping_setter).collect();
 
+    let mapping_helper_implementations: Vec<TokenStream> =
+        mapping_types.iter().map(generate_mapping_helpers).collect();
+
+    let (state_fields, state_keys, state_getters): (Vec<_>, Vec<_>, Vec<_>) = mapping_types
+        .iter()
+        .map(|types| {
+            (
+                Ident::new(&types.mapping_name, Span::call_site()),
+                &types.key_type,
+                &types.try_getter_name,
+            )
+        })
+        .multiunzip();
+    let state_entries: Vec<&Ident> = mapping_types
+        .iter()
+        .map(|types| &types.try_entries_name)
+        .collect();
+
     let new_implementation = generate_new(&deployment_calls, &dependency_ids);
 
+    // The fuzzer can only generate calls to functions whose inputs are all plaintext.
+    let fuzzer_implementation = function_types
+        .iter()
+        .any(|types| types.plaintext_inputs)
+        .then(|| {
+            quote! {
+                /// A fuzzer calling random functions of this program as `actors`, starting from the current state.
+                pub fn fuzzer(&self, actors: &[Account<N>]) -> Fuzzer<N, #call_enum<N>, Self> {
+                    let program_id = self.program_id;
+                    Fuzzer::new(
+                        &self.vm_manager,
+                        actors,
+                        move |vm_manager| Self { vm_manager, program_id, _network: std::marker::PhantomData },
+                        Self::call,
+                    )
+                }
+            }
+        });
+
     quote! {
         #[allow(unused_imports)]
         use snarkvm::console::program::{Record, Plaintext};
@@ -135,15 +202,59 @@ fn generate_program_impl(
                 *self.program_id.name()
             }
 
+            /// Execute a queued call, returning the outputs unconverted.
+            pub fn call(&self, account: &Account<N>, call: &#call_enum<N>) -> Result<Vec<CallOutput<N>>, anyhow::Error> {
+                let (function_name, function_args) = call.encode();
+                let dependencies: Vec<ProgramID<N>> = vec![#(ProgramID::try_from(#dependency_ids).expect("invalid program ID")),*];
+
+                Ok(self
+                    .vm_manager
+                    .execute_and_broadcast(
+                        account,
+                        &self.program_id,
+                        &function_name,
+                        function_args,
+                        &dependencies,
+                    )?)
+            }
+
             #(#function_implementations)*
 
             #(#view_implementations)*
 
             #(#mapping_implementations)*
+
+            /// The values at the given keys of each mapping, skipping keys without a value.
+            pub fn state_for(&self, #(#state_fields: &[#state_keys]),*) -> Result<#state_name<N>, anyhow::Error> {
+                Ok(#state_name {
+                    #(#state_fields: {
+                        let mut entries = IndexMap::new();
+                        for key in #state_fields {
+                            if let Some(value) = self.#state_getters(*key)? {
+                                entries.insert(*key, value);
+                            }
+                        }
+                        entries
+                    },)*
+                    _network: std::marker::PhantomData,
+                })
+            }
         }
 
         impl<N: Network> #program_struct<N, LocalVM> where LocalVM: VMManager<N>{
             #(#mapping_setter_implementations)*
+
+            #(#mapping_helper_implementations)*
+
+            /// Every entry of every mapping. Fails on a fork, which only knows the keys it pulled.
+            pub fn state(&self) -> Result<#state_name<N>, anyhow::Error> {
+                Ok(#state_name {
+                    #(#state_fields: self.#state_entries()?.into_iter().collect(),)*
+                    _network: std::marker::PhantomData,
+                })
+            }
+
+            #fuzzer_implementation
         }
     }
 }
@@ -331,11 +442,35 @@ pub fn generate_records(records: &[Record]) -> Vec<TokenStream> {
                     }
                 }
 
+                impl<N: Network> ProgramRecord for #record_name<N> {
+                    type Network = N;
+                }
+
             }
         })
         .collect()
 }
 
+/// A proptest strategy building `build` from the `Arbitrary` strategy of every field.
+///
+/// The field strategies are nested in pairs, as proptest only implements `Strategy` for short tuples.
+fn generate_fields_strategy(
+    names: &[Ident],
+    types: &[TokenStream],
+    build: TokenStream,
+) -> TokenStream {
+    let (strategy, pattern) = names.iter().zip(types).fold(
+        (quote! { Just(()) }, quote! { () }),
+        |(strategy, pattern), (name, ty)| {
+            (
+                quote! { (#strategy, <#ty as Arbitrary<N>>::strategy(actors)) },
+                quote! { (#pattern, #name) },
+            )
+        },
+    );
+    quote! { #strategy.prop_map(|#pattern| #build) }
+}
+
 pub fn generate_structs(structs: &[leo_abi_types::Struct]) -> Vec<TokenStream> {
     structs
         .iter()
@@ -346,7 +481,7 @@ pub fn generate_structs(structs: &[leo_abi_types::Struct]) -> Vec<TokenStream> {
                 .expect("Struct.path should have at least one segment");
             let struct_name = Ident::new(&last.to_case(Pascal), Span::call_site());
 
-            let (definitions, extractions, names, constructor_definitions, conversions): (Vec<_>, Vec<_>, Vec<_>, Vec<_>, Vec<_>) = struct_def
+            let (definitions, extractions, names, types, constructor_definitions, conversions): (Vec<_>, Vec<_>, Vec<_>, Vec<_>, Vec<_>, Vec<_>) = struct_def
                 .fields
                 .iter()
                 .map(|field| {
@@ -377,13 +512,19 @@ pub fn generate_structs(structs: &[leo_abi_types::Struct]) -> Vec<TokenStream> {
                         )
                     };
 
-                    (definition, extraction, name, constructor_definition, conversion)
+                    (definition, extraction, name, member_type, constructor_definition, conversion)
                 })
                 .multiunzip();
 
+            let strategy = generate_fields_strategy(
+                &names,
+                &types,
+                quote! { Self { #(#names,)* _network: std::marker::PhantomData } },
+            );
+
             quote! {
                 /// Struct from Leo.
-                #[derive(Debug, Clone, Copy)]
+                #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
                 pub struct #struct_name<N: Network> {
                     #(#definitions)*
                     _network: std::marker::PhantomData<N>
@@ -415,6 +556,13 @@ pub fn generate_structs(structs: &[leo_abi_types::Struct]) -> Vec<TokenStream> {
                     }
                 }
 
+                impl<N: Network> Arbitrary<N> for #struct_name<N> {
+                    #[allow(unused_variables)]
+                    fn strategy(actors: &Actors<N>) -> BoxedStrategy<Self> {
+                        #strategy.boxed()
+                    }
+                }
+
                 impl<N: Network> #struct_name<N> {
                     pub fn new(#(#constructor_definitions)*) -> Self {
                         Self {
@@ -430,15 +578,29 @@ pub fn generate_structs(structs: &[leo_abi_types::Struct]) -> Vec<TokenStream> {
 
 pub(crate) struct FunctionTypes {
     pub(crate) name: Ident,
+    pub(crate) input_names: Vec<Ident>,
+    pub(crate) input_types: Vec<TokenStream>,
     pub(crate) input_params: TokenStream,
     pub(crate) input_conversions: TokenStream,
+    /// Whether every input is plaintext, so the fuzzer can generate the call.
+    pub(crate) plaintext_inputs: bool,
+    pub(crate) output_type: TokenStream,
     pub(crate) return_type: TokenStream,
     pub(crate) return_conversions: TokenStream,
 }
 
 pub(crate) struct MappingTypes {
     pub(crate) getter_name: Ident,
+    pub(crate) try_getter_name: Ident,
     pub(crate) setter_name: Ident,
+    pub(crate) try_setter_name: Ident,
+    pub(crate) contains_name: Ident,
+    pub(crate) entries_name: Ident,
+    pub(crate) try_entries_name: Ident,
+    pub(crate) remove_name: Ident,
+    pub(crate) try_remove_name: Ident,
+    pub(crate) len_name: Ident,
+    pub(crate) try_len_name: Ident,
     pub(crate) mapping_name: String,
     pub(crate) key_type: TokenStream,
     pub(crate) value_type: TokenStream,
@@ -448,70 +610,96 @@ fn generate_function_types(functions: &[leo_abi_types::Function]) -> Vec<Functio
     functions.iter().map(|function| {
         let name = Ident::new(&function.name, Span::call_site());
 
-        let (input_params, input_conversions): (Vec<_>, Vec<_>) = function.inputs.iter().enumerate().map(|(i, input)| {
+        let (input_names, input_types, input_params, input_conversions): (Vec<_>, Vec<_>, Vec<_>, Vec<_>) = function.inputs.iter().enumerate().map(|(i, input)| {
             let param_name = Ident::new(&format!("arg{}", i+1), Span::call_site());
             let param_type = input.to_rust_type();
             let param = quote! { #param_name: #param_type };
             let conversion = quote! { (#param_name).to_value() };
-            (param, conversion)
-        }).unzip();
+            (param_name, param_type, param, conversion)
+        }).multiunzip();
         let input_params = quote! { #(#input_params),* };
         let input_conversions = quote! { #(#input_conversions),* };
-
-        let (return_type, return_conversions) = match function.outputs.len() {
+        let plaintext_inputs = function
+            .inputs
+            .iter()
+            .all(|input| matches!(input, FunctionInput::Plaintext { .. }));
+
+        let (output_types, output_conversions): (Vec<_>, Vec<_>) = function.outputs.iter()
+            .enumerate()
+            .map(|(i, output)| generate_output_conversion(i, output))
+            .unzip();
+        let (output_type, return_conversions) = match function.outputs.len() {
             0 => (
-                quote! { Result<(), anyhow::Error> },
+                quote! { () },
                 quote! { Ok(()) }
             ),
-            1 => {
-                let output_type = function.outputs[0].to_rust_type();
-                let conversion = quote! {
-                    match function_outputs.get(0) {
-                        Some(snarkvm_value) => <#output_type>::from_value(snarkvm_value.clone()),
-                        None => return Err(anyhow!("Missing output")),
-                    }
-                };
-                (
-                    quote! { Result<#output_type, anyhow::Error> },
-                    quote! { Ok(#conversion) }
-                )
-            },
-            _ => {
-                let (output_types, output_conversions): (Vec<_>, Vec<_>) = function.outputs.iter()
-                    .enumerate()
-                    .map(|(i, output)| {
-                        let output_type = output.to_rust_type();
-                        let conversion = quote! {
-                            match function_outputs.get(#i) {
-                                Some(snarkvm_value) => <#output_type>::from_value(snarkvm_value.clone()),
-                                None => return Err(anyhow!("Missing output")),
-                            }
-                        };
-                        (output_type, conversion)
-                    })
-                    .unzip();
-                (
-                    quote! { Result<(#(#output_types),*), anyhow::Error> },
-                    quote! { Ok((#(#output_conversions),*)) }
-                )
-            }
+            1 => (
+                quote! { #(#output_types)* },
+                quote! { Ok(#(#output_conversions)*) }
+            ),
+            _ => (
+                quote! { (#(#output_types),*) },
+                quote! { Ok((#(#output_conversions),*)) }
+            ),
         };
+        let return_type = quote! { Result<#output_type, anyhow::Error> };
         FunctionTypes {
             name,
+            input_names,
+            input_types,
             input_params,
             input_conversions,
+            plaintext_inputs,
+            output_type,
             return_type,
             return_conversions,
         }
     }).collect()
 }
 
+/// Records owned by another account are returned encrypted, so record outputs are wrapped in a `RecordOutput`.
+fn generate_output_conversion(index: usize, output: &FunctionOutput) -> (TokenStream, TokenStream) {
+    let output_type = output.to_rust_type();
+    match output {
+        FunctionOutput::Record(_) => (
+            quote! { RecordOutput<#output_type> },
+            quote! {
+                match function_outputs.get(#index) {
+                    Some(output) => RecordOutput::<#output_type>::from_output(output.clone()),
+                    None => return Err(anyhow!("Missing output")),
+                }
+            },
+        ),
+        _ => (
+            output_type.clone(),
+            quote! {
+                match function_outputs.get(#index) {
+                    Some(output) => <#output_type>::from_value(output.clone().into_value()?),
+                    None => return Err(anyhow!("Missing output")),
+                }
+            },
+        ),
+    }
+}
+
 fn generate_mapping_types(mappings: &[leo_abi_types::Mapping]) -> Vec<MappingTypes> {
     mappings
         .iter()
         .map(|mapping| MappingTypes {
             getter_name: Ident::new(&format!("get_{}", mapping.name), Span::call_site()),
+            try_getter_name: Ident::new(&format!("try_get_{}", mapping.name), Span::call_site()),
             setter_name: Ident::new(&format!("set_{}", mapping.name), Span::call_site()),
+            try_setter_name: Ident::new(&format!("try_set_{}", mapping.name), Span::call_site()),
+            contains_name: Ident::new(&format!("contains_{}", mapping.name), Span::call_site()),
+            entries_name: Ident::new(&format!("entries_{}", mapping.name), Span::call_site()),
+            try_entries_name: Ident::new(
+                &format!("try_entries_{}", mapping.name),
+                Span::call_site(),
+            ),
+            remove_name: Ident::new(&format!("remove_{}", mapping.name), Span::call_site()),
+            try_remove_name: Ident::new(&format!("try_remove_{}", mapping.name), Span::call_site()),
+            len_name: Ident::new(&format!("len_{}", mapping.name), Span::call_site()),
+            try_len_name: Ident::new(&format!("try_len_{}", mapping.name), Span::call_site()),
             mapping_name: mapping.name.clone(),
             key_type: mapping.key.to_rust_type(),
             value_type: mapping.value.to_rust_type(),
@@ -568,14 +756,291 @@ fn generate_new(deployment_calls: &[TokenStream], dependency_ids: &[TokenStream]
     }
 }
 
+/// One variant per function, with the inputs as named fields.
+fn generate_call_enum(
+    function_types: &[FunctionTypes],
+    call_enum: &Ident,
+    program_id: &Literal,
+) -> TokenStream {
+    let (variants, encode_arms, decode_arms): (Vec<_>, Vec<_>, Vec<_>) = function_types
+        .iter()
+        .map(|types| {
+            let FunctionTypes {
+                name,
+                input_names,
+                input_types,
+                input_params,
+                input_conversions,
+                ..
+            } = types;
+            let variant = Ident::new(&name.to_string().to_case(Pascal), Span::call_site());
+            let indices = 0..input_names.len();
+
+            let definition = quote! { #variant { #input_params } };
+            let encode_arm = quote! {
+                Self::#variant { #(#input_names),* } => (
+                    Identifier::try_from(stringify!(#name)).expect("invalid identifier"),
+                    vec![#input_conversions],
+                )
+            };
+            let decode_arm = quote! {
+                stringify!(#name) => Ok(Self::#variant {
+                    #(#input_names: <#input_types>::from_value(input(#indices)?)),*
+                })
+            };
+            (definition, encode_arm, decode_arm)
+        })
+        .multiunzip();
+
+    let strategies: Vec<_> = function_types
+        .iter()
+        .filter(|types| types.plaintext_inputs)
+        .map(|types| {
+            let name = types.name.to_string();
+            let variant = Ident::new(&name.to_case(Pascal), Span::call_site());
+            let input_names = &types.input_names;
+            let strategy = generate_fields_strategy(
+                input_names,
+                &types.input_types,
+                quote! { Self::#variant { #(#input_names),* } },
+            );
+            quote! { (#name, #strategy.boxed()) }
+        })
+        .collect();
+
+    let fuzz_implementation = (!strategies.is_empty()).then(|| {
+        quote! {
+            /// Generated calls to the functions whose inputs are all plaintext.
+            impl<N: Network> FuzzCall<N> for #call_enum<N> {
+                fn strategies(actors: &Actors<N>) -> Vec<(&'static str, BoxedStrategy<Self>)> {
+                    vec![#(#strategies),*]
+                }
+            }
+        }
+    });
+
+    quote! {
+        /// A call to one of the program functions.
+        #[derive(Debug, Clone)]
+        pub enum #call_enum<N: Network> {
+            #(#variants,)*
+            #[doc(hidden)]
+            _Network(std::convert::Infallible, std::marker::PhantomData<N>),
+        }
+
+        impl<N: Network> #call_enum<N> {
+            /// The function name and arguments of the call.
+            pub fn encode(&self) -> (Identifier<N>, Vec<Value<N>>) {
+                match self {
+                    #(#encode_arms,)*
+                    Self::_Network(never, _) => match *never {},
+                }
+            }
+
+            /// Decode a transition of the program, failing if an input is not public.
+            pub fn decode(transition: &Transition<N>) -> Result<Self, anyhow::Error> {
+                Self::from_inputs(
+                    transition.program_id(),
+                    transition.function_name(),
+                    &ProgramCall::public_inputs(transition),
+                )
+            }
+
+            #[allow(unused_variables)]
+            fn from_inputs(
+                program_id: &ProgramID<N>,
+                function: &Identifier<N>,
+                inputs: &[Option<Value<N>>],
+            ) -> Result<Self, anyhow::Error> {
+                if program_id.to_string() != #program_id {
+                    return Err(anyhow!("Expected a call to {}, got {program_id}", #program_id));
+                }
+                let input = |index: usize| {
+                    inputs
+                        .get(index)
+                        .cloned()
+                        .flatten()
+                        .ok_or_else(|| anyhow!("Input {index} of {program_id}/{function} is not public"))
+                };
+                match function.to_string().as_str() {
+                    #(#decode_arms,)*
+                    function => Err(anyhow!("Unknown function {}/{function}", #program_id)),
+                }
+            }
+        }
+
+        /// Decode an indexed call, failing if an input is not public.
+        impl<N: Network> DecodeCall<N> for #call_enum<N> {
+            fn decode(call: &ProgramCall<N>) -> leo_bindings_sdk::Result<Self> {
+                Ok(Self::from_inputs(&call.program_id, &call.function, &call.inputs)?)
+            }
+        }
+
+        impl<N: Network> std::fmt::Display for #call_enum<N> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let (function, args) = self.encode();
+                let args: Vec<String> = args.iter().map(ToString::to_string).collect();
+                write!(f, "{}/{function}({})", #program_id, args.join(", "))
+            }
+        }
+
+        #fuzz_implementation
+    }
+}
+
+/// One variant per mapping, holding a written key with its old and new values.
+fn generate_write_enum(
+    mapping_types: &[MappingTypes],
+    write_enum: &Ident,
+    program_id: &Literal,
+) -> TokenStream {
+    let (variants, decode_arms): (Vec<_>, Vec<_>) = mapping_types
+        .iter()
+        .map(|types| {
+            let MappingTypes {
+                mapping_name,
+                key_type,
+                value_type,
+                ..
+            } = types;
+            let variant = Ident::new(&mapping_name.to_case(Pascal), Span::call_site());
+
+            let definition = quote! {
+                #variant { key: #key_type, old: Option<#value_type>, new: Option<#value_type> }
+            };
+            let decode_arm = quote! {
+                #mapping_name => Some(Self::#variant {
+                    key: <#key_type>::from_value(Value::Plaintext(write.key.clone())),
+                    old: write.old.clone().map(<#value_type>::from_value),
+                    new: write.new.clone().map(<#value_type>::from_value),
+                })
+            };
+            (definition, decode_arm)
+        })
+        .unzip();
+
+    quote! {
+        /// A write to one of the program mappings.
+        #[derive(Debug, Clone)]
+        pub enum #write_enum<N: Network> {
+            #(#variants,)*
+            #[doc(hidden)]
+            _Network(std::convert::Infallible, std::marker::PhantomData<N>),
+        }
+
+        impl<N: Network> #write_enum<N> {
+            /// Decode a write, or `None` if it is not to a mapping of this program.
+            pub fn decode(write: &MappingWrite<N>) -> Option<Self> {
+                if write.program_id.to_string() != #program_id {
+                    return None;
+                }
+                match write.mapping.to_string().as_str() {
+                    #(#decode_arms,)*
+                    _ => None,
+                }
+            }
+
+            /// Decode the writes to mappings of this program, skipping the others.
+            pub fn decode_all(writes: &[MappingWrite<N>]) -> Vec<Self> {
+                writes.iter().filter_map(Self::decode).collect()
+            }
+        }
+    }
+}
+
+fn generate_state(mapping_types: &[MappingTypes], state: &Ident, diff: &Ident) -> TokenStream {
+    let (fields, names, key_types, value_types): (Vec<_>, Vec<_>, Vec<_>, Vec<_>) = mapping_types
+        .iter()
+        .map(|types| {
+            (
+                Ident::new(&types.mapping_name, Span::call_site()),
+                &types.mapping_name,
+                &types.key_type,
+                &types.value_type,
+            )
+        })
+        .multiunzip();
+
+    quote! {
+        /// The entries of every program mapping.
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct #state<N: Network> {
+            #(pub #fields: IndexMap<#key_types, #value_types>,)*
+            #[doc(hidden)]
+            pub _network: std::marker::PhantomData<N>,
+        }
+
+        impl<N: Network> Default for #state<N> {
+            fn default() -> Self {
+                Self {
+                    #(#fields: IndexMap::new(),)*
+                    _network: std::marker::PhantomData,
+                }
+            }
+        }
+
+        impl<N: Network> #state<N> {
+            /// The entries added, removed and changed from this state to `other`.
+            #[allow(unused_variables)]
+            pub fn diff(&self, other: &Self) -> #diff<N> {
+                #diff {
+                    #(#fields: MappingDiff::new(&self.#fields, &other.#fields),)*
+                    _network: std::marker::PhantomData,
+                }
+            }
+
+            /// Panic listing the differing entries if `other` is not equal to this state.
+            #[track_caller]
+            pub fn assert_unchanged(&self, other: &Self) {
+                let diff = self.diff(other);
+                assert!(diff.is_empty(), "program state changed:\n{diff}");
+            }
+        }
+
+        /// The entries of every program mapping that differ between two states.
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct #diff<N: Network> {
+            #(pub #fields: MappingDiff<#key_types, #value_types>,)*
+            #[doc(hidden)]
+            pub _network: std::marker::PhantomData<N>,
+        }
+
+        impl<N: Network> #diff<N> {
+            pub fn is_empty(&self) -> bool {
+                true #(&& self.#fields.is_empty())*
+            }
+        }
+
+        impl<N: Network> std::fmt::Display for #diff<N> {
+            #[allow(unused_variables)]
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                #(
+                    if !self.#fields.is_empty() {
+                        writeln!(f, "{}:", #names)?;
+                        write!(f, "{}", self.#fields)?;
+                    }
+                )*
+                Ok(())
+            }
+        }
+    }
+}
+
 fn generate_function(dependency_ids: &[TokenStream], types: &FunctionTypes) -> TokenStream {
     let FunctionTypes {
         name,
         input_params,
         input_conversions,
+        output_type,
         return_type,
         return_conversions,
+        ..
     } = types;
+    let sponsored_name = Ident::new(&format!("{name}_sponsored"), Span::call_site());
+    let authorize_name = Ident::new(&format!("authorize_{name}"), Span::call_site());
+    let broadcast_name = Ident::new(&format!("broadcast_{name}"), Span::call_site());
+    let estimate_name = Ident::new(&format!("estimate_{name}"), Span::call_site());
+    let simulate_name = Ident::new(&format!("simulate_{name}"), Span::call_site());
 
     quote! {
         pub fn #name(&self, account: &Account<N>, #input_params) -> #return_type {
@@ -595,6 +1060,99 @@ fn generate_function(dependency_ids: &[TokenStream], types: &FunctionTypes) -> T
 
             #return_conversions
         }
+
+        /// Call the function signed by `account`, with the fee paid by `fee_payer`.
+        pub fn #sponsored_name(&self, account: &Account<N>, fee_payer: &Account<N>, #input_params) -> #return_type {
+            let function_name = Identifier::try_from(stringify!(#name)).expect("invalid identifier");
+            let function_args: Vec<Value<N>> = vec![#input_conversions];
+            let dependencies: Vec<ProgramID<N>> = vec![#(ProgramID::try_from(#dependency_ids).expect("invalid program ID")),*];
+
+            let function_outputs = self
+                .vm_manager
+                .execute_sponsored(
+                    account,
+                    fee_payer,
+                    &self.program_id,
+                    &function_name,
+                    function_args,
+                    &dependencies,
+                )?;
+
+            #return_conversions
+        }
+
+        /// Sign a call to the function and its fee without submitting it.
+        pub fn #authorize_name(&self, account: &Account<N>, #input_params) -> Result<AuthorizationBundle<N>, anyhow::Error> {
+            let function_name = Identifier::try_from(stringify!(#name)).expect("invalid identifier");
+            let function_args: Vec<Value<N>> = vec![#input_conversions];
+            let dependencies: Vec<ProgramID<N>> = vec![#(ProgramID::try_from(#dependency_ids).expect("invalid program ID")),*];
+
+            Ok(self
+                .vm_manager
+                .authorize_call(
+                    account,
+                    &self.program_id,
+                    &function_name,
+                    function_args,
+                    &dependencies,
+                )?)
+        }
+
+        /// Execute and finalize a call to the function without committing it, returning the outputs and mapping writes.
+        ///
+        /// Simulation runs on a `LocalVM`. `NetworkVm` always returns an error, so to simulate
+        /// against network state, bind the program to `LocalVM::fork(&client)` instead.
+        pub fn #simulate_name(&self, account: &Account<N>, #input_params) -> Result<(#output_type, Vec<MappingWrite<N>>), anyhow::Error> {
+            let function_name = Identifier::try_from(stringify!(#name)).expect("invalid identifier");
+            let function_args: Vec<Value<N>> = vec![#input_conversions];
+            let dependencies: Vec<ProgramID<N>> = vec![#(ProgramID::try_from(#dependency_ids).expect("invalid program ID")),*];
+
+            let simulation = self
+                .vm_manager
+                .simulate(
+                    account,
+                    &self.program_id,
+                    &function_name,
+                    function_args,
+                    &dependencies,
+                )?;
+
+            #[allow(unused_variables)]
+            fn outputs<N: Network>(function_outputs: &[CallOutput<N>]) -> #return_type {
+                #return_conversions
+            }
+
+            Ok((outputs(&simulation.outputs)?, simulation.writes))
+        }
+
+        /// Estimate the cost of a call to the function without submitting it.
+        pub fn #estimate_name(&self, account: &Account<N>, #input_params) -> Result<ExecutionStats, anyhow::Error> {
+            let function_name = Identifier::try_from(stringify!(#name)).expect("invalid identifier");
+            let function_args: Vec<Value<N>> = vec![#input_conversions];
+            let dependencies: Vec<ProgramID<N>> = vec![#(ProgramID::try_from(#dependency_ids).expect("invalid program ID")),*];
+
+            Ok(self
+                .vm_manager
+                .estimate_execution_fee(
+                    account,
+                    &self.program_id,
+                    &function_name,
+                    function_args,
+                    &dependencies,
+                )?)
+        }
+
+        /// Prove and submit a signed call to the function.
+        pub fn #broadcast_name(&self, bundle: &AuthorizationBundle<N>) -> #return_type {
+            let function_name = Identifier::try_from(stringify!(#name)).expect("invalid identifier");
+            bundle.ensure_call(&self.program_id, &function_name)?;
+
+            let function_outputs = self
+                .vm_manager
+                .broadcast_authorization(bundle)?;
+
+            #return_conversions
+        }
     }
 }
 
@@ -605,6 +1163,7 @@ fn generate_view_function(types: &FunctionTypes) -> TokenStream {
         input_conversions,
         return_type,
         return_conversions,
+        ..
     } = types;
 
     quote! {
@@ -612,9 +1171,12 @@ fn generate_view_function(types: &FunctionTypes) -> TokenStream {
             let function_name = Identifier::try_from(stringify!(#name)).expect("invalid identifier");
             let function_args: Vec<Value<N>> = vec![#input_conversions];
 
-            let function_outputs = self
+            let function_outputs: Vec<CallOutput<N>> = self
                 .vm_manager
-                .evaluate_view(&self.program_id, &function_name, function_args)?;
+                .evaluate_view(&self.program_id, &function_name, function_args)?
+                .into_iter()
+                .map(CallOutput::from)
+                .collect();
 
             #return_conversions
         }
@@ -624,6 +1186,8 @@ fn generate_view_function(types: &FunctionTypes) -> TokenStream {
 fn generate_mapping(types: &MappingTypes) -> TokenStream {
     let MappingTypes {
         getter_name,
+        try_getter_name,
+        contains_name,
         mapping_name,
         key_type,
         value_type,
@@ -631,28 +1195,37 @@ fn generate_mapping(types: &MappingTypes) -> TokenStream {
     } = types;
 
     quote! {
-        pub fn #getter_name(&self, key: #key_type) -> Option<#value_type> {
+        /// The value at `key`, failing if the VM could not be queried.
+        pub fn #try_getter_name(&self, key: #key_type) -> Result<Option<#value_type>, anyhow::Error> {
             let key_value: Value<N> = key.to_value();
             let mapping_id = Identifier::try_from(#mapping_name).expect("invalid identifier");
 
-            match self
+            Ok(self
                 .vm_manager
-                .mapping_value(&self.program_id, &mapping_id, &key_value)
-            {
-                Ok(Some(val)) => Some(<#value_type>::from_value(val)),
-                Ok(None) => None,
+                .mapping_value(&self.program_id, &mapping_id, &key_value)?
+                .map(<#value_type>::from_value))
+        }
+
+        pub fn #getter_name(&self, key: #key_type) -> Option<#value_type> {
+            match self.#try_getter_name(key) {
+                Ok(value) => value,
                 Err(e) => {
                     log::error!("Failed to fetch mapping value: {}", e);
                     None
                 }
             }
         }
+
+        pub fn #contains_name(&self, key: #key_type) -> Result<bool, anyhow::Error> {
+            Ok(self.#try_getter_name(key)?.is_some())
+        }
     }
 }
 
 fn generate_mapping_setter(types: &MappingTypes) -> TokenStream {
     let MappingTypes {
         setter_name,
+        try_setter_name,
         mapping_name,
         key_type,
         value_type,
@@ -660,11 +1233,68 @@ fn generate_mapping_setter(types: &MappingTypes) -> TokenStream {
     } = types;
 
     quote! {
-        pub fn #setter_name(&self, key: #key_type, value: #value_type) {
+        pub fn #try_setter_name(&self, key: #key_type, value: #value_type) -> Result<(), anyhow::Error> {
             let key_value: Value<N> = key.to_value();
             let val_value: Value<N> = value.to_value();
             let mapping_id = Identifier::try_from(#mapping_name).expect("invalid identifier");
-            self.vm_manager.set_mapping_value(&self.program_id, &mapping_id, &key_value, &val_value).unwrap();
+            Ok(self.vm_manager.set_mapping_value(&self.program_id, &mapping_id, &key_value, &val_value)?)
+        }
+
+        pub fn #setter_name(&self, key: #key_type, value: #value_type) {
+            self.#try_setter_name(key, value).unwrap();
+        }
+    }
+}
+
+fn generate_mapping_helpers(types: &MappingTypes) -> TokenStream {
+    let MappingTypes {
+        entries_name,
+        try_entries_name,
+        remove_name,
+        try_remove_name,
+        len_name,
+        try_len_name,
+        mapping_name,
+        key_type,
+        value_type,
+        ..
+    } = types;
+
+    quote! {
+        /// Every entry of the mapping. Fails on a fork, which only knows the keys it pulled.
+        pub fn #try_entries_name(&self) -> Result<Vec<(#key_type, #value_type)>, anyhow::Error> {
+            let mapping_id = Identifier::try_from(#mapping_name).expect("invalid identifier");
+            Ok(self
+                .vm_manager
+                .mapping_entries(&self.program_id, &mapping_id)?
+                .into_iter()
+                .map(|(key, value)| (<#key_type>::from_value(key), <#value_type>::from_value(value)))
+                .collect())
+        }
+
+        pub fn #entries_name(&self) -> Vec<(#key_type, #value_type)> {
+            self.#try_entries_name().unwrap()
+        }
+
+        /// Remove `key`, returning whether it was present.
+        pub fn #try_remove_name(&self, key: #key_type) -> Result<bool, anyhow::Error> {
+            let key_value: Value<N> = key.to_value();
+            let mapping_id = Identifier::try_from(#mapping_name).expect("invalid identifier");
+            Ok(self.vm_manager.remove_mapping_value(&self.program_id, &mapping_id, &key_value)?)
+        }
+
+        pub fn #remove_name(&self, key: #key_type) -> bool {
+            self.#try_remove_name(key).unwrap()
+        }
+
+        /// The number of entries in the mapping. Fails on a fork, like the entries.
+        pub fn #try_len_name(&self) -> Result<usize, anyhow::Error> {
+            let mapping_id = Identifier::try_from(#mapping_name).expect("invalid identifier");
+            Ok(self.vm_manager.mapping_entries(&self.program_id, &mapping_id)?.len())
+        }
+
+        pub fn #len_name(&self) -> usize {
+            self.#try_len_name().unwrap()
         }
     }
 }
@@ -690,7 +1320,7 @@ pub fn generate_interface_module(iface: &Interface) -> TokenStream {
         #[allow(non_snake_case)]
         pub mod #module {
             #[allow(unused_imports)]
-            use leo_bindings_sdk::{Account, Address, FromValue, LocalVM, ToValue, VMManager, anyhow, log, snarkvm, indexmap::IndexMap};
+            use leo_bindings_sdk::{Account, Address, Actors, Arbitrary, AuthorizationBundle, CallOutput, ExecutionStats, FromValue, LocalVM, MappingWrite, ProgramRecord, RecordOutput, ToValue, VMManager, anyhow, log, snarkvm, indexmap::IndexMap, proptest::strategy::{BoxedStrategy, Just, Strategy}};
             use anyhow::{anyhow, Result};
             use snarkvm::prelude::*;
             #[allow(unused_imports)]
@@ -850,6 +1480,10 @@ fn generate_record_view(record: &Record) -> TokenStream {
                 Ok(<Self as FromValue<N>>::from_value(Value::DynamicRecord(value)))
             }
         }
+
+        impl<N: Network> ProgramRecord for #record_name<N> {
+            type Network = N;
+        }
     }
 }
 
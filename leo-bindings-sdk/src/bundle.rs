@@ -0,0 +1,105 @@
+use crate::error::{Error, Result};
+use crate::output::CallOutput;
+use serde::{Deserialize, Serialize};
+use snarkvm::prelude::{Authorization, Identifier, Network, ProgramID};
+use std::fmt;
+use std::str::FromStr;
+
+/// A signed call and fee that can be proven and broadcast on another machine.
+///
+/// The bundle serializes to JSON, so it can be signed on an offline host
+/// and submitted by a service that does not hold the private key.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct AuthorizationBundle<N: Network> {
+    program_id: ProgramID<N>,
+    function_name: Identifier<N>,
+    dependencies: Vec<ProgramID<N>>,
+    authorization: Authorization<N>,
+    fee_authorization: Authorization<N>,
+    outputs: Vec<CallOutput<N>>,
+}
+
+impl<N: Network> AuthorizationBundle<N> {
+    pub(crate) fn new(
+        program_id: ProgramID<N>,
+        function_name: Identifier<N>,
+        dependencies: Vec<ProgramID<N>>,
+        authorization: Authorization<N>,
+        fee_authorization: Authorization<N>,
+        outputs: Vec<CallOutput<N>>,
+    ) -> Self {
+        Self {
+            program_id,
+            function_name,
+            dependencies,
+            authorization,
+            fee_authorization,
+            outputs,
+        }
+    }
+
+    pub fn program_id(&self) -> &ProgramID<N> {
+        &self.program_id
+    }
+
+    pub fn function_name(&self) -> &Identifier<N> {
+        &self.function_name
+    }
+
+    pub fn dependencies(&self) -> &[ProgramID<N>] {
+        &self.dependencies
+    }
+
+    pub fn authorization(&self) -> &Authorization<N> {
+        &self.authorization
+    }
+
+    pub fn fee_authorization(&self) -> &Authorization<N> {
+        &self.fee_authorization
+    }
+
+    /// The outputs the call will produce, as seen by the signer.
+    pub fn outputs(&self) -> &[CallOutput<N>] {
+        &self.outputs
+    }
+
+    /// Fail if the bundle does not call `program_id.function_name`.
+    pub fn ensure_call(
+        &self,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+    ) -> Result<()> {
+        if self.program_id != *program_id || self.function_name != *function_name {
+            return Err(Error::Other(format!(
+                "Authorization is for '{}/{}', expected '{program_id}/{function_name}'",
+                self.program_id, self.function_name
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl<N: Network> fmt::Debug for AuthorizationBundle<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuthorizationBundle")
+            .field("program_id", &self.program_id)
+            .field("function_name", &self.function_name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<N: Network> fmt::Display for AuthorizationBundle<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let json = serde_json::to_string(self).map_err(|_| fmt::Error)?;
+        f.write_str(&json)
+    }
+}
+
+impl<N: Network> FromStr for AuthorizationBundle<N> {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(serde_json::from_str(s)?)
+    }
+}
@@ -1,10 +1,16 @@
 use crate::error::{Error, Result};
-use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
+use crate::failover::{EndpointHeadersMiddleware, Endpoints, FailoverMiddleware};
+use crate::retry::{RetryAfterMiddleware, RetryStrategy};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest_middleware::ClientWithMiddleware;
 use reqwest_retry::{RetryTransientMiddleware, policies::ExponentialBackoff};
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::sync::RwLock;
 
+const DEFAULT_JWT_URL: &str = "https://api.provable.com/jwts";
+const DEFAULT_PROVER_URL: &str = "https://api.provable.com/prove";
+
 #[derive(Debug, Clone)]
 struct JwtToken {
     token: String,
@@ -15,6 +21,7 @@ struct JwtToken {
 pub struct Credentials {
     consumer_id: String,
     api_key: String,
+    jwt_url: String,
     jwt_token: Arc<RwLock<Option<JwtToken>>>,
 }
 
@@ -23,10 +30,17 @@ impl Credentials {
         Self {
             consumer_id: consumer_id.to_string(),
             api_key: api_key.to_string(),
+            jwt_url: DEFAULT_JWT_URL.to_string(),
             jwt_token: Arc::new(RwLock::new(None)),
         }
     }
 
+    /// Fetch JWTs from `{jwt_url}/{consumer_id}` instead of the Provable API.
+    pub fn with_jwt_url(mut self, jwt_url: &str) -> Self {
+        self.jwt_url = jwt_url.trim_end_matches('/').to_string();
+        self
+    }
+
     pub fn from_env() -> Result<Self> {
         dotenvy::dotenv().ok();
         let consumer_id = std::env::var("PROVABLE_CONSUMER_ID").map_err(|_| {
@@ -35,11 +49,15 @@ impl Credentials {
         let api_key = std::env::var("PROVABLE_API_KEY").map_err(|_| {
             Error::Config("PROVABLE_API_KEY environment variable not set".to_string())
         })?;
-        Ok(Self::new(&consumer_id, &api_key))
+        let credentials = Self::new(&consumer_id, &api_key);
+        Ok(match std::env::var("PROVABLE_JWT_URL") {
+            Ok(jwt_url) => credentials.with_jwt_url(&jwt_url),
+            Err(_) => credentials,
+        })
     }
 
     async fn fetch_jwt(&self, client: &ClientWithMiddleware) -> Result<JwtToken> {
-        let url = format!("https://api.provable.com/jwts/{}", self.consumer_id);
+        let url = format!("{}/{}", self.jwt_url, self.consumer_id);
 
         let response = client
             .post(&url)
@@ -115,38 +133,256 @@ impl Credentials {
     }
 }
 
+/// How long to poll for confirmations, and how often.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Polling {
+    pub(crate) transaction_timeout: Duration,
+    pub(crate) program_timeout: Duration,
+    pub(crate) initial_interval: Duration,
+    pub(crate) max_interval: Duration,
+}
+
 #[derive(Clone, Debug)]
 pub struct Client {
     pub(crate) client: ClientWithMiddleware,
     pub(crate) endpoint: String,
     pub(crate) credentials: Option<Credentials>,
+    pub(crate) prover_url: String,
+    pub(crate) prover_broadcast: bool,
+    pub(crate) polling: Polling,
+    pub(crate) endpoints: Arc<Endpoints>,
+    pub(crate) read_quorum: usize,
 }
 
-impl Client {
-    pub fn new(endpoint: &str, credentials: Option<Credentials>) -> Result<Self> {
-        if endpoint.is_empty() {
-            return Err(Error::Config("Endpoint is required".to_string()));
+/// Configures the HTTP client behind a `Client`.
+///
+/// ```ignore
+/// let client = Client::builder("https://rpc.example.com")
+///     .header("X-API-Key", &api_key)
+///     .timeout(Duration::from_secs(10))
+///     .max_retries(5)
+///     .transaction_timeout(Duration::from_secs(300))
+///     .build()?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct ClientBuilder {
+    endpoint: String,
+    fallbacks: Vec<String>,
+    read_quorum: usize,
+    credentials: Option<Credentials>,
+    timeout: Duration,
+    connect_timeout: Option<Duration>,
+    max_retries: u32,
+    min_backoff: Duration,
+    max_backoff: Duration,
+    max_retry_after: Duration,
+    polling: Polling,
+    headers: Vec<(String, String)>,
+    proxy: Option<String>,
+}
+
+impl ClientBuilder {
+    fn new(endpoint: &str) -> Self {
+        Self {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            fallbacks: Vec::new(),
+            read_quorum: 1,
+            credentials: None,
+            timeout: Duration::from_secs(30),
+            connect_timeout: None,
+            max_retries: 3,
+            min_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+            max_retry_after: Duration::from_secs(60),
+            polling: Polling {
+                transaction_timeout: Duration::from_secs(120),
+                program_timeout: Duration::from_secs(60),
+                initial_interval: Duration::from_secs(1),
+                max_interval: Duration::from_secs(5),
+            },
+            headers: Vec::new(),
+            proxy: None,
         }
+    }
 
-        let retry_policy = ExponentialBackoff::builder()
-            .retry_bounds(Duration::from_millis(500), Duration::from_secs(10))
-            .build_with_max_retries(3);
+    /// Send requests to `endpoint` when the endpoints before it fail.
+    pub fn fallback(mut self, endpoint: &str) -> Self {
+        self.fallbacks.push(endpoint.trim_end_matches('/').to_string());
+        self
+    }
 
-        let reqwest_client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(30))
+    /// Read mappings from every endpoint and require `quorum` of them to agree.
+    pub fn read_quorum(mut self, quorum: usize) -> Self {
+        self.read_quorum = quorum;
+        self
+    }
+
+    pub fn credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
+
+    /// Timeout of a single request, 30 seconds by default.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Retries of failed requests and rate limited responses, 3 by default.
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Bounds of the exponential backoff between retries, 500ms to 10s by default.
+    pub fn retry_backoff(mut self, min: Duration, max: Duration) -> Self {
+        self.min_backoff = min;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Longest `Retry-After` delay to wait for before retrying, 60 seconds by default.
+    ///
+    /// Longer delays fail with `Error::RateLimited`.
+    pub fn max_retry_after(mut self, max_delay: Duration) -> Self {
+        self.max_retry_after = max_delay;
+        self
+    }
+
+    /// How long to wait for a transaction to be confirmed, 120 seconds by default.
+    pub fn transaction_timeout(mut self, timeout: Duration) -> Self {
+        self.polling.transaction_timeout = timeout;
+        self
+    }
+
+    /// How long to wait for a deployed program to be available, 60 seconds by default.
+    pub fn program_timeout(mut self, timeout: Duration) -> Self {
+        self.polling.program_timeout = timeout;
+        self
+    }
+
+    /// The polling interval starts at `initial` and doubles up to `max`, 1s to 5s by default.
+    pub fn poll_interval(mut self, initial: Duration, max: Duration) -> Self {
+        self.polling.initial_interval = initial;
+        self.polling.max_interval = max;
+        self
+    }
+
+    /// Send `name: value` with every request to the endpoint and its fallbacks, such as
+    /// the API key of an RPC provider. JWT and proving requests never carry it.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Send all requests through the proxy at `url`.
+    pub fn proxy(mut self, url: &str) -> Self {
+        self.proxy = Some(url.to_string());
+        self
+    }
+
+    pub fn build(self) -> Result<Client> {
+        if self.endpoint.is_empty() {
+            return Err(Error::Config("Endpoint is required".to_string()));
+        }
+        let endpoints: Vec<String> = std::iter::once(self.endpoint.clone())
+            .chain(self.fallbacks)
+            .collect();
+        if self.read_quorum > endpoints.len() {
+            return Err(Error::Config(format!(
+                "Read quorum {} exceeds the {} configured endpoints",
+                self.read_quorum,
+                endpoints.len()
+            )));
+        }
+        let endpoints = Arc::new(Endpoints::new(endpoints));
+
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| Error::Config(format!("Invalid header name '{name}': {e}")))?;
+            let value = HeaderValue::from_str(value)
+                .map_err(|e| Error::Config(format!("Invalid value for header '{name}': {e}")))?;
+            headers.append(name, value);
+        }
+
+        let mut reqwest_builder = reqwest::Client::builder().timeout(self.timeout);
+        if let Some(connect_timeout) = self.connect_timeout {
+            reqwest_builder = reqwest_builder.connect_timeout(connect_timeout);
+        }
+        if let Some(proxy) = &self.proxy {
+            let proxy = reqwest::Proxy::all(proxy)
+                .map_err(|e| Error::Config(format!("Invalid proxy '{proxy}': {e}")))?;
+            reqwest_builder = reqwest_builder.proxy(proxy);
+        }
+        let reqwest_client = reqwest_builder
             .build()
             .map_err(|e| Error::Other(e.to_string()))?;
 
-        let client = ClientBuilder::new(reqwest_client)
-            .with(RetryTransientMiddleware::new_with_policy(retry_policy))
+        let retry_policy = ExponentialBackoff::builder()
+            .retry_bounds(self.min_backoff, self.max_backoff)
+            .build_with_max_retries(self.max_retries);
+
+        let client = reqwest_middleware::ClientBuilder::new(reqwest_client)
+            .with(RetryAfterMiddleware {
+                max_retries: self.max_retries,
+                max_delay: self.max_retry_after,
+            })
+            .with(RetryTransientMiddleware::new_with_policy_and_strategy(
+                retry_policy,
+                RetryStrategy,
+            ))
+            .with(FailoverMiddleware {
+                endpoints: endpoints.clone(),
+            })
+            .with(EndpointHeadersMiddleware {
+                endpoints: endpoints.clone(),
+                headers,
+            })
             .build();
 
-        Ok(Self {
+        Ok(Client {
             client,
-            endpoint: endpoint.to_string(),
-            credentials,
+            endpoint: self.endpoint,
+            credentials: self.credentials,
+            prover_url: DEFAULT_PROVER_URL.to_string(),
+            prover_broadcast: false,
+            polling: self.polling,
+            endpoints,
+            read_quorum: self.read_quorum,
         })
     }
+}
+
+impl Client {
+    pub fn new(endpoint: &str, credentials: Option<Credentials>) -> Result<Self> {
+        let builder = Self::builder(endpoint);
+        match credentials {
+            Some(credentials) => builder.credentials(credentials).build(),
+            None => builder.build(),
+        }
+    }
+
+    pub fn builder(endpoint: &str) -> ClientBuilder {
+        ClientBuilder::new(endpoint)
+    }
+
+    /// Send proving requests to `{prover_url}/{network}/prove` instead of the Provable API.
+    pub fn with_prover_url(mut self, prover_url: &str) -> Self {
+        self.prover_url = prover_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Let the prover broadcast the transactions it proves.
+    pub fn with_prover_broadcast(mut self, broadcast: bool) -> Self {
+        self.prover_broadcast = broadcast;
+        self
+    }
 
     pub fn from_env() -> Result<Self> {
         let _ = dotenvy::dotenv();
@@ -158,7 +394,10 @@ impl Client {
         let api_key = std::env::var("PROVABLE_API_KEY").ok();
 
         let credentials = match (consumer_id, api_key) {
-            (Some(cid), Some(key)) => Some(Credentials::new(&cid, &key)),
+            (Some(cid), Some(key)) => Some(match std::env::var("PROVABLE_JWT_URL") {
+                Ok(jwt_url) => Credentials::new(&cid, &key).with_jwt_url(&jwt_url),
+                Err(_) => Credentials::new(&cid, &key),
+            }),
             (None, None) => None,
             _ => {
                 return Err(Error::Config(
@@ -167,7 +406,11 @@ impl Client {
             }
         };
 
-        Self::new(&endpoint, credentials)
+        let client = Self::new(&endpoint, credentials)?;
+        Ok(match std::env::var("PROVER_URL") {
+            Ok(prover_url) => client.with_prover_url(&prover_url),
+            Err(_) => client,
+        })
     }
 
     pub fn endpoint(&self) -> &str {
@@ -178,6 +421,14 @@ impl Client {
         self.credentials.is_some()
     }
 
+    pub fn prover_url(&self) -> &str {
+        &self.prover_url
+    }
+
+    pub fn prover_broadcast(&self) -> bool {
+        self.prover_broadcast
+    }
+
     pub(crate) async fn get_valid_jwt_token(&self) -> Result<String> {
         let credentials = self
             .credentials
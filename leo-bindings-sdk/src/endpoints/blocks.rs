@@ -1,7 +1,12 @@
 use crate::config::Client;
 use crate::error::{Error, Result};
+use crate::retry::retry_after;
+use snarkvm::ledger::block::{Block, Transactions};
+use snarkvm::ledger::committee::Committee;
 use snarkvm::prelude::Network;
 
+const MAX_BLOCK_RANGE: u32 = 10;
+
 impl Client {
     /// Get the latest block height
     ///
@@ -18,6 +23,8 @@ impl Client {
                 .trim()
                 .parse()
                 .map_err(|_| Error::Other("Invalid block height format".to_string()))
+        } else if let Some(retry_after) = retry_after(&response) {
+            Err(Error::RateLimited(Some(retry_after)))
         } else {
             let status = response.status().as_u16();
             let message = response
@@ -32,25 +39,82 @@ impl Client {
     ///
     /// GET /{network}/block/{height}
     ///
-    pub async fn block<N: Network>(&self, height: u32) -> Result<String> {
+    pub async fn block<N: Network>(&self, height: u32) -> Result<Block<N>> {
         let url = format!("{}/v2/{}/block/{}", self.endpoint, N::SHORT_NAME, height);
+        self.get_json(&url, &format!("Block {height}")).await
+    }
 
-        let response = self.client.get(&url).send().await?;
+    /// Get a block by hash
+    ///
+    /// GET /{network}/block/{hash}
+    ///
+    pub async fn block_by_hash<N: Network>(&self, hash: &N::BlockHash) -> Result<Block<N>> {
+        let url = format!("{}/v2/{}/block/{}", self.endpoint, N::SHORT_NAME, hash);
+        self.get_json(&url, &format!("Block {hash}")).await
+    }
 
-        if response.status().is_success() {
-            response
-                .text()
-                .await
-                .map_err(|e| Error::Other(e.to_string()))
-        } else if response.status() == 404 {
-            Err(Error::NotFound(format!("Block {} not found", height)))
-        } else {
-            let status = response.status().as_u16();
-            let message = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            Err(Error::Other(format!("API error {status}: {message}")))
+    /// Get the latest block
+    ///
+    /// GET /{network}/block/latest
+    ///
+    pub async fn latest_block<N: Network>(&self) -> Result<Block<N>> {
+        let url = format!("{}/v2/{}/block/latest", self.endpoint, N::SHORT_NAME);
+        self.get_json(&url, "Latest block").await
+    }
+
+    /// Get the blocks in `start..end`
+    ///
+    /// GET /{network}/blocks?start={start}&end={end}
+    ///
+    pub async fn blocks<N: Network>(&self, start: u32, end: u32) -> Result<Vec<Block<N>>> {
+        let mut blocks = Vec::new();
+        // Nodes serve at most 10 blocks per request.
+        for chunk_start in (start..end).step_by(MAX_BLOCK_RANGE as usize) {
+            let chunk_end = end.min(chunk_start + MAX_BLOCK_RANGE);
+            let url = format!(
+                "{}/v2/{}/blocks?start={}&end={}",
+                self.endpoint,
+                N::SHORT_NAME,
+                chunk_start,
+                chunk_end
+            );
+            let chunk: Vec<Block<N>> = self
+                .get_json(&url, &format!("Blocks {chunk_start}..{chunk_end}"))
+                .await?;
+            blocks.extend(chunk);
         }
+        Ok(blocks)
+    }
+
+    /// Get the confirmed transactions of a block
+    ///
+    /// GET /{network}/block/{height}/transactions
+    ///
+    pub async fn block_transactions<N: Network>(&self, height: u32) -> Result<Transactions<N>> {
+        let url = format!(
+            "{}/v2/{}/block/{}/transactions",
+            self.endpoint,
+            N::SHORT_NAME,
+            height
+        );
+        self.get_json(&url, &format!("Block {height}")).await
+    }
+
+    /// Get the latest state root
+    ///
+    /// GET /{network}/stateRoot/latest
+    ///
+    pub async fn state_root<N: Network>(&self) -> Result<N::StateRoot> {
+        let url = format!("{}/v2/{}/stateRoot/latest", self.endpoint, N::SHORT_NAME);
+        self.get_json(&url, "State root").await
+    }
+
+    /// Get the latest committee
+    ///
+    /// GET /{network}/committee/latest
+    ///
+    pub async fn committee<N: Network>(&self) -> Result<Committee<N>> {
+        let url = format!("{}/v2/{}/committee/latest", self.endpoint, N::SHORT_NAME);
+        self.get_json(&url, "Committee").await
     }
 }
@@ -1,7 +1,8 @@
 use crate::Address;
 use crate::config::Client;
 use crate::error::{Error, Result};
-use snarkvm::prelude::{Literal, Network, Plaintext, Value};
+use crate::failover::{Pinned, Served, ServedBy};
+use snarkvm::prelude::{Identifier, Literal, Network, Plaintext, Value};
 
 impl Client {
     /// Query a mapping value from the network
@@ -14,26 +15,161 @@ impl Client {
         mapping_name: &str,
         key: &Value<N>,
     ) -> Result<Option<Value<N>>> {
+        Ok(self
+            .mapping_served::<N>(program_id, mapping_name, key)
+            .await?
+            .value)
+    }
+
+    /// Query a mapping value along with the endpoints that served it
+    ///
+    /// With a read quorum, every endpoint is queried and at least
+    /// `read_quorum` of them must return the same value.
+    ///
+    /// GET /{network}/program/{program}/mapping/{mapping}/{key}
+    ///
+    pub async fn mapping_served<N: Network>(
+        &self,
+        program_id: &str,
+        mapping_name: &str,
+        key: &Value<N>,
+    ) -> Result<Served<Option<Value<N>>>> {
+        if self.read_quorum <= 1 {
+            return self
+                .mapping_from::<N>(&self.endpoint, program_id, mapping_name, key, false)
+                .await;
+        }
+
+        let mut reads = tokio::task::JoinSet::new();
+        for endpoint in self.endpoints() {
+            let client = self.clone();
+            let endpoint = endpoint.clone();
+            let (program_id, mapping_name, key) = (
+                program_id.to_string(),
+                mapping_name.to_string(),
+                key.clone(),
+            );
+            reads.spawn(async move {
+                let read = client
+                    .mapping_from::<N>(&endpoint, &program_id, &mapping_name, &key, true)
+                    .await;
+                (endpoint, read)
+            });
+        }
+
+        let mut votes: Vec<Served<Option<Value<N>>>> = Vec::new();
+        let mut errors = Vec::new();
+        while let Some(read) = reads.join_next().await {
+            match read {
+                Ok((_, Ok(served))) => {
+                    match votes.iter_mut().find(|vote| vote.value == served.value) {
+                        Some(vote) => vote.endpoints.extend(served.endpoints),
+                        None => votes.push(served),
+                    }
+                }
+                Ok((endpoint, Err(e))) => errors.push(format!("{endpoint}: {e}")),
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+
+        votes.sort_by_key(|vote| std::cmp::Reverse(vote.endpoints.len()));
+        match votes.into_iter().next() {
+            Some(vote) if vote.endpoints.len() >= self.read_quorum => Ok(vote),
+            vote => Err(Error::Other(format!(
+                "Read quorum of {} not reached for {program_id}/{mapping_name}: {} endpoints agree, errors: [{}]",
+                self.read_quorum,
+                vote.map_or(0, |vote| vote.endpoints.len()),
+                errors.join(", ")
+            ))),
+        }
+    }
+
+    async fn mapping_from<N: Network>(
+        &self,
+        endpoint: &str,
+        program_id: &str,
+        mapping_name: &str,
+        key: &Value<N>,
+        pinned: bool,
+    ) -> Result<Served<Option<Value<N>>>> {
         let key_str = key.to_string().replace("\"", "");
 
         let url = format!(
             "{}/v2/{}/program/{}/mapping/{}/{}",
-            self.endpoint,
+            endpoint,
             N::SHORT_NAME,
             program_id,
             mapping_name,
             key_str
         );
 
-        let response = self.client.get(&url).send().await?;
+        let request = self.client.get(&url);
+        let request = if pinned {
+            request.with_extension(Pinned)
+        } else {
+            request
+        };
+        let response = request.send().await?;
+        let endpoints = vec![match response.extensions().get::<ServedBy>() {
+            Some(ServedBy(served_by)) => served_by.clone(),
+            None => endpoint.to_string(),
+        }];
 
         if response.status().is_success() {
             let json_text = response.text().await?;
 
-            let value_opt: Option<Value<N>> = serde_json::from_str(&json_text)?;
-            Ok(value_opt)
+            let value: Option<Value<N>> = serde_json::from_str(&json_text)?;
+            Ok(Served { value, endpoints })
         } else if response.status() == 404 {
-            Ok(None)
+            Ok(Served {
+                value: None,
+                endpoints,
+            })
+        } else {
+            let status = response.status().as_u16();
+            let message = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            Err(Error::Other(format!("API error {status}: {message}")))
+        }
+    }
+
+    /// Query a mapping value from the network with the height it was read at
+    ///
+    /// GET /{network}/program/{program}/mapping/{mapping}/{key}?metadata=true
+    ///
+    pub async fn mapping_with_height<N: Network>(
+        &self,
+        program_id: &str,
+        mapping_name: &str,
+        key: &Value<N>,
+    ) -> Result<(Option<Value<N>>, u32)> {
+        let key_str = key.to_string().replace("\"", "");
+
+        let url = format!(
+            "{}/v2/{}/program/{}/mapping/{}/{}?metadata=true",
+            self.endpoint,
+            N::SHORT_NAME,
+            program_id,
+            mapping_name,
+            key_str
+        );
+
+        let response = self.client.get(&url).send().await?;
+
+        if response.status().is_success() {
+            let json: serde_json::Value = response.json().await?;
+            let value: Option<Value<N>> = serde_json::from_value(
+                json.get("data")
+                    .cloned()
+                    .ok_or_else(|| Error::Other("Missing data field".to_string()))?,
+            )?;
+            let height = json
+                .get("height")
+                .and_then(|h| h.as_u64())
+                .ok_or_else(|| Error::Other("Missing height field".to_string()))?;
+            Ok((value, height as u32))
         } else {
             let status = response.status().as_u16();
             let message = response
@@ -44,6 +180,20 @@ impl Client {
         }
     }
 
+    /// List the mapping names of a program
+    ///
+    /// GET /{network}/program/{id}/mappings
+    ///
+    pub async fn mapping_names<N: Network>(&self, program_id: &str) -> Result<Vec<Identifier<N>>> {
+        let url = format!(
+            "{}/v2/{}/program/{}/mappings",
+            self.endpoint,
+            N::SHORT_NAME,
+            program_id
+        );
+        self.get_json(&url, &format!("Program {program_id}")).await
+    }
+
     pub async fn public_balance<N: Network>(&self, address: &Address<N>) -> Result<u64> {
         let key = Value::from(Literal::Address(**address));
         let balance = self.mapping::<N>("credits.aleo", "account", &key).await?;
@@ -4,3 +4,30 @@ pub mod programs;
 pub mod proving;
 pub mod transactions;
 pub mod view;
+
+use crate::config::Client;
+use crate::error::{Error, Result};
+use crate::retry::retry_after;
+use serde::de::DeserializeOwned;
+
+impl Client {
+    /// GET `url` and parse the JSON response, mapping a 404 to `Error::NotFound`.
+    pub(crate) async fn get_json<T: DeserializeOwned>(&self, url: &str, what: &str) -> Result<T> {
+        let response = self.client.get(url).send().await?;
+
+        if response.status().is_success() {
+            Ok(response.json().await?)
+        } else if response.status() == 404 {
+            Err(Error::NotFound(format!("{what} not found")))
+        } else if let Some(retry_after) = retry_after(&response) {
+            Err(Error::RateLimited(Some(retry_after)))
+        } else {
+            let status = response.status().as_u16();
+            let message = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            Err(Error::Other(format!("API error {status}: {message}")))
+        }
+    }
+}
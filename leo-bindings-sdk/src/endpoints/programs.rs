@@ -2,7 +2,6 @@ use crate::config::Client;
 use crate::error::{Error, Result};
 use crate::utils::poll_until;
 use snarkvm::prelude::Network;
-use std::time::Duration;
 
 impl Client {
     /// Fetch a program's bytecode from the network
@@ -115,8 +114,9 @@ impl Client {
                     }
                 }
             },
-            Duration::from_secs(60),
-            Duration::from_secs(1),
+            self.polling.program_timeout,
+            self.polling.initial_interval,
+            self.polling.max_interval,
         )
         .await
         .map_err(|_| Error::ProgramTimeout(program_id.to_string()))
@@ -1,40 +1,71 @@
 use crate::config::Client;
 use crate::error::{Error, Result};
+use crate::retry::retry_after;
 use serde::{Deserialize, Serialize};
 use snarkvm::prelude::{Authorization, Network, Transaction};
 
-#[derive(Debug, Serialize)]
-struct ProvingRequest {
-    authorization: serde_json::Value,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    fee_authorization: Option<serde_json::Value>,
-    broadcast: bool,
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct ProvingRequest {
+    pub(crate) authorization: serde_json::Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) fee_authorization: Option<serde_json::Value>,
+    pub(crate) broadcast: bool,
 }
 
-#[derive(Debug, Deserialize)]
-struct ProvingResponse {
-    transaction: serde_json::Value,
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct ProvingResponse {
+    pub(crate) transaction: serde_json::Value,
 }
 
 impl Client {
     /// Submit an authorization for delegated proving
     ///
+    /// The prover also broadcasts the transaction if `with_prover_broadcast` is set.
+    ///
+    /// POST {prover_url}/{network}/prove
+    ///
     pub async fn prove<N: Network>(
         &self,
         authorization: &Authorization<N>,
+    ) -> Result<Transaction<N>> {
+        self.submit_proving_request(authorization, None).await
+    }
+
+    /// Submit an authorization with a fee authorization for delegated proving
+    ///
+    /// POST {prover_url}/{network}/prove
+    ///
+    pub async fn prove_with_fee<N: Network>(
+        &self,
+        authorization: &Authorization<N>,
+        fee_authorization: &Authorization<N>,
+    ) -> Result<Transaction<N>> {
+        self.submit_proving_request(authorization, Some(fee_authorization))
+            .await
+    }
+
+    async fn submit_proving_request<N: Network>(
+        &self,
+        authorization: &Authorization<N>,
+        fee_authorization: Option<&Authorization<N>>,
     ) -> Result<Transaction<N>> {
         let jwt_token = self.get_valid_jwt_token().await?;
 
         let authorization_json = serde_json::to_value(authorization)
             .map_err(|e| Error::Other(format!("Failed to serialize authorization: {}", e)))?;
 
+        let fee_authorization_json = fee_authorization
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(|e| Error::Other(format!("Failed to serialize fee authorization: {}", e)))?;
+
         let proving_request = ProvingRequest {
             authorization: authorization_json,
-            fee_authorization: None,
-            broadcast: false,
+            fee_authorization: fee_authorization_json,
+            broadcast: self.prover_broadcast,
         };
 
-        let url = format!("https://api.provable.com/prove/{}/prove", N::SHORT_NAME);
+        let url = format!("{}/{}/prove", self.prover_url, N::SHORT_NAME);
 
         let request_body = serde_json::to_string(&proving_request)
             .map_err(|e| Error::Other(format!("Failed to serialize proving request: {}", e)))?;
@@ -50,6 +81,7 @@ impl Client {
             .await?;
 
         if !response.status().is_success() {
+            let retry_after = retry_after(&response);
             let status = response.status().as_u16();
             let message = response
                 .text()
@@ -59,7 +91,7 @@ impl Client {
             return match status {
                 401 => Err(Error::Unauthorized),
                 400 => Err(Error::Other(message)),
-                429 => Err(Error::RateLimited(None)),
+                429 => Err(Error::RateLimited(retry_after)),
                 _ => Err(Error::Other(format!("API error {status}: {message}"))),
             };
         }
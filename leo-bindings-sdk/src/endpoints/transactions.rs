@@ -1,8 +1,9 @@
 use crate::config::Client;
 use crate::error::{Error, Result};
+use crate::retry::retry_after;
 use crate::utils::poll_until;
+use snarkvm::ledger::block::ConfirmedTransaction;
 use snarkvm::prelude::{Network, Transaction};
-use std::time::Duration;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TransactionStatus {
@@ -51,6 +52,7 @@ impl Client {
         if response.status().is_success() {
             Ok(())
         } else {
+            let retry_after = retry_after(&response);
             let status = response.status().as_u16();
             let message = response
                 .text()
@@ -60,12 +62,46 @@ impl Client {
             match status {
                 400 => Err(Error::Other(message)),
                 401 => Err(Error::Unauthorized),
-                429 => Err(Error::RateLimited(None)),
+                429 => Err(Error::RateLimited(retry_after)),
                 _ => Err(Error::Other(format!("API error {status}: {message}"))),
             }
         }
     }
 
+    /// Fetch a transaction
+    ///
+    /// GET /{network}/transaction/{id}
+    ///
+    pub async fn transaction<N: Network>(
+        &self,
+        tx_id: &N::TransactionID,
+    ) -> Result<Transaction<N>> {
+        let url = format!(
+            "{}/v2/{}/transaction/{}",
+            self.endpoint,
+            N::SHORT_NAME,
+            tx_id
+        );
+        self.get_json(&url, &format!("Transaction {tx_id}")).await
+    }
+
+    /// Fetch a confirmed transaction with its finalize operations
+    ///
+    /// GET /{network}/transaction/confirmed/{id}
+    ///
+    pub async fn confirmed_transaction<N: Network>(
+        &self,
+        tx_id: &N::TransactionID,
+    ) -> Result<ConfirmedTransaction<N>> {
+        let url = format!(
+            "{}/v2/{}/transaction/confirmed/{}",
+            self.endpoint,
+            N::SHORT_NAME,
+            tx_id
+        );
+        self.get_json(&url, &format!("Transaction {tx_id}")).await
+    }
+
     /// Query the status of a transaction
     ///
     /// GET /{network}/transaction/confirmed/{id}
@@ -159,8 +195,9 @@ impl Client {
                     }
                 }
             },
-            Duration::from_secs(120),
-            Duration::from_secs(1),
+            self.polling.transaction_timeout,
+            self.polling.initial_interval,
+            self.polling.max_interval,
         )
         .await
         .map_err(|_| Error::TransactionTimeout(tx_id_owned))
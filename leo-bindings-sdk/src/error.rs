@@ -24,19 +24,56 @@ pub enum Error {
     #[error("Invalid configuration: {0}")]
     Config(String),
 
+    /// A request that failed before reaching the node, or without a response.
+    #[error("{0}")]
+    Network(String),
+
+    #[error("Cost budget of {budget} microcredits exceeded, {spent} spent and {fee} more needed")]
+    BudgetExceeded { budget: u64, spent: u64, fee: u64 },
+
+    #[error("Cost of {call} rose from {snapshot} to {cost} microcredits")]
+    CostIncreased {
+        call: String,
+        snapshot: u64,
+        cost: u64,
+    },
+
     #[error("{0}")]
     Other(String),
 }
 
+impl Error {
+    /// Whether the same request may succeed when retried.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Error::Network(_) | Error::RateLimited(_))
+    }
+
+    /// Prefix the message with `context`, keeping transient errors transient.
+    pub(crate) fn context(self, context: &str) -> Self {
+        match self {
+            Error::Network(e) => Error::Network(format!("{context}: {e}")),
+            Error::RateLimited(_) => self,
+            e => Error::Other(format!("{context}: {e}")),
+        }
+    }
+}
+
 impl From<reqwest::Error> for Error {
     fn from(e: reqwest::Error) -> Self {
-        Error::Other(e.to_string())
+        if e.is_decode() || e.is_builder() {
+            Error::Other(e.to_string())
+        } else {
+            Error::Network(e.to_string())
+        }
     }
 }
 
 impl From<reqwest_middleware::Error> for Error {
     fn from(e: reqwest_middleware::Error) -> Self {
-        Error::Other(e.to_string())
+        match e {
+            reqwest_middleware::Error::Reqwest(e) => e.into(),
+            e => Error::Network(e.to_string()),
+        }
     }
 }
 
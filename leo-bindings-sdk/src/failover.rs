@@ -0,0 +1,230 @@
+use crate::config::Client;
+use http::Extensions;
+use reqwest::header::HeaderMap;
+use reqwest::{Request, Response, StatusCode, Url};
+use reqwest_middleware::{Middleware, Next};
+use snarkvm::prelude::Network;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long a failed endpoint is skipped before it is tried again first.
+const UNHEALTHY_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// How many blocks an endpoint may lag behind the others in `Client::check_health`.
+const MAX_HEIGHT_LAG: u32 = 10;
+
+/// The endpoints of a `Client` in order of preference, with their health.
+#[derive(Debug)]
+pub(crate) struct Endpoints {
+    urls: Vec<String>,
+    unhealthy_until: Mutex<Vec<Option<Instant>>>,
+}
+
+impl Endpoints {
+    pub(crate) fn new(urls: Vec<String>) -> Self {
+        let unhealthy_until = Mutex::new(vec![None; urls.len()]);
+        Self {
+            urls,
+            unhealthy_until,
+        }
+    }
+
+    pub(crate) fn urls(&self) -> &[String] {
+        &self.urls
+    }
+
+    /// Endpoint indices, healthy ones first, each in configured order.
+    fn order(&self) -> Vec<usize> {
+        let now = Instant::now();
+        let unhealthy_until = self.unhealthy_until.lock().unwrap();
+        let (healthy, unhealthy): (Vec<usize>, Vec<usize>) = (0..self.urls.len())
+            .partition(|&i| unhealthy_until[i].is_none_or(|until| until <= now));
+        healthy.into_iter().chain(unhealthy).collect()
+    }
+
+    fn mark_failed(&self, index: usize) {
+        log::warn!("Endpoint {} marked unhealthy", self.urls[index]);
+        self.unhealthy_until.lock().unwrap()[index] = Some(Instant::now() + UNHEALTHY_COOLDOWN);
+    }
+
+    fn mark_healthy(&self, index: usize) {
+        self.unhealthy_until.lock().unwrap()[index] = None;
+    }
+}
+
+/// The endpoint that served a response, stored in the response extensions.
+#[derive(Clone, Debug)]
+pub(crate) struct ServedBy(pub(crate) String);
+
+/// Marks a request that must only be sent to the endpoint in its URL.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Pinned;
+
+/// Sends requests for the primary endpoint to the first healthy endpoint,
+/// moving on to the next one on transport errors and 5xx responses.
+pub(crate) struct FailoverMiddleware {
+    pub(crate) endpoints: Arc<Endpoints>,
+}
+
+#[async_trait::async_trait]
+impl Middleware for FailoverMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let url = req.url().to_string();
+        let primary = &self.endpoints.urls()[0];
+        let path = match url.strip_prefix(primary.as_str()) {
+            Some(path)
+                if (path.is_empty() || path.starts_with('/'))
+                    && extensions.get::<Pinned>().is_none() =>
+            {
+                path.to_string()
+            }
+            _ => return next.run(req, extensions).await,
+        };
+
+        let order = self.endpoints.order();
+        let mut last = None;
+        for (attempt, &index) in order.iter().enumerate() {
+            let is_last = attempt + 1 == order.len();
+            let endpoint = &self.endpoints.urls()[index];
+            let Some(mut request) = req.try_clone() else {
+                return next.run(req, extensions).await;
+            };
+            *request.url_mut() = Url::parse(&format!("{endpoint}{path}"))
+                .map_err(reqwest_middleware::Error::middleware)?;
+
+            match next.clone().run(request, extensions).await {
+                Ok(mut response) if !response.status().is_server_error() || is_last => {
+                    if !response.status().is_server_error() {
+                        self.endpoints.mark_healthy(index);
+                    }
+                    log::debug!("{path} served by {endpoint}");
+                    response.extensions_mut().insert(ServedBy(endpoint.clone()));
+                    return Ok(response);
+                }
+                Ok(response) => {
+                    // Nodes answer some missing resources with a 500, so only gateway errors count as failures.
+                    if matches!(
+                        response.status(),
+                        StatusCode::BAD_GATEWAY
+                            | StatusCode::SERVICE_UNAVAILABLE
+                            | StatusCode::GATEWAY_TIMEOUT
+                    ) {
+                        self.endpoints.mark_failed(index);
+                    }
+                }
+                Err(e) => {
+                    self.endpoints.mark_failed(index);
+                    last = Some(e);
+                }
+            }
+        }
+        Err(last.unwrap_or_else(|| {
+            reqwest_middleware::Error::middleware(std::io::Error::other("No endpoints configured"))
+        }))
+    }
+}
+
+/// Adds the `ClientBuilder::header` headers to requests for the configured endpoints,
+/// so they never reach the JWT or proving hosts.
+pub(crate) struct EndpointHeadersMiddleware {
+    pub(crate) endpoints: Arc<Endpoints>,
+    pub(crate) headers: HeaderMap,
+}
+
+#[async_trait::async_trait]
+impl Middleware for EndpointHeadersMiddleware {
+    async fn handle(
+        &self,
+        mut req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let url = req.url().as_str();
+        let is_endpoint = self.endpoints.urls().iter().any(|endpoint| {
+            url.strip_prefix(endpoint.as_str())
+                .is_some_and(|path| path.is_empty() || path.starts_with(['/', '?']))
+        });
+        if is_endpoint {
+            for (name, value) in &self.headers {
+                req.headers_mut().append(name, value.clone());
+            }
+        }
+        next.run(req, extensions).await
+    }
+}
+
+/// A response along with the endpoints that served it.
+#[derive(Clone, Debug)]
+pub struct Served<T> {
+    pub value: T,
+    pub endpoints: Vec<String>,
+}
+
+/// The state of one endpoint, as seen by `Client::check_health`.
+#[derive(Clone, Debug)]
+pub struct EndpointHealth {
+    pub endpoint: String,
+    pub height: Option<u32>,
+    pub latency: Duration,
+    pub error: Option<String>,
+}
+
+impl EndpointHealth {
+    pub fn is_healthy(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+impl Client {
+    /// The primary endpoint followed by the fallback endpoints.
+    pub fn endpoints(&self) -> &[String] {
+        self.endpoints.urls()
+    }
+
+    /// Query the latest height of every endpoint and update their health.
+    ///
+    /// Endpoints that fail or lag more than 10 blocks behind the highest
+    /// one are skipped by later requests until they recover.
+    pub async fn check_health<N: Network>(&self) -> Vec<EndpointHealth> {
+        let mut health = Vec::new();
+        for endpoint in self.endpoints.urls() {
+            let start = Instant::now();
+            let url = format!("{endpoint}/v2/{}/block/height/latest", N::SHORT_NAME);
+            let height = match self.client.get(&url).with_extension(Pinned).send().await {
+                Ok(response) if response.status().is_success() => response
+                    .text()
+                    .await
+                    .map_err(|e| e.to_string())
+                    .and_then(|text| text.trim().parse::<u32>().map_err(|e| e.to_string())),
+                Ok(response) => Err(format!("API error {}", response.status().as_u16())),
+                Err(e) => Err(e.to_string()),
+            };
+            health.push(EndpointHealth {
+                endpoint: endpoint.clone(),
+                height: height.as_ref().ok().copied(),
+                latency: start.elapsed(),
+                error: height.err(),
+            });
+        }
+
+        let max_height = health.iter().filter_map(|h| h.height).max();
+        for (index, endpoint) in health.iter_mut().enumerate() {
+            if let (Some(height), Some(max_height)) = (endpoint.height, max_height)
+                && height + MAX_HEIGHT_LAG < max_height
+            {
+                endpoint.error = Some(format!("Lagging at height {height}, expected {max_height}"));
+            }
+            if endpoint.is_healthy() {
+                self.endpoints.mark_healthy(index);
+            } else {
+                self.endpoints.mark_failed(index);
+            }
+        }
+        health
+    }
+}
@@ -0,0 +1,164 @@
+use crate::account::Account;
+use crate::address::Address;
+use crate::error::{Error, Result};
+use crate::output::record_owner;
+use crate::value::{FromValue, ToValue};
+use crate::vm_manager::VMManager;
+use snarkvm::ledger::block::{Output, Transaction};
+use snarkvm::prelude::{Entry, Identifier, Literal, Network, Plaintext, ProgramID, Record, Value};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+
+/// A source of unspent `credits.aleo` records for paying fees privately.
+pub trait RecordSource<N: Network>: Send + Sync {
+    /// Unspent `credits.aleo` records owned by `account`.
+    fn unspent_credits(&self, account: &Account<N>) -> Result<Vec<Record<N, Plaintext<N>>>>;
+
+    /// Called after a transaction paying its fee with `spent` was accepted.
+    fn fee_paid(&self, _spent: &Record<N, Plaintext<N>>, _change: Option<Record<N, Plaintext<N>>>) {
+    }
+}
+
+/// An in-memory list of `credits.aleo` records, updated as fees are paid.
+#[derive(Clone)]
+pub struct CreditsRecords<N: Network> {
+    records: Arc<RwLock<Vec<Record<N, Plaintext<N>>>>>,
+}
+
+impl<N: Network> CreditsRecords<N> {
+    pub fn new(records: Vec<Record<N, Plaintext<N>>>) -> Self {
+        Self {
+            records: Arc::new(RwLock::new(records)),
+        }
+    }
+
+    pub fn add(&self, record: Record<N, Plaintext<N>>) {
+        self.records.write().unwrap().push(record);
+    }
+
+    pub fn records(&self) -> Vec<Record<N, Plaintext<N>>> {
+        self.records.read().unwrap().clone()
+    }
+}
+
+impl<N: Network> Default for CreditsRecords<N> {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl<N: Network> fmt::Debug for CreditsRecords<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CreditsRecords")
+            .field("records", &self.records.read().unwrap().len())
+            .finish()
+    }
+}
+
+impl<N: Network> RecordSource<N> for CreditsRecords<N> {
+    fn unspent_credits(&self, account: &Account<N>) -> Result<Vec<Record<N, Plaintext<N>>>> {
+        let records = self.records.read().unwrap();
+        let mut owned = Vec::new();
+        for record in records.iter() {
+            if record_owner(record)? == account.address() {
+                owned.push(record.clone());
+            }
+        }
+        Ok(owned)
+    }
+
+    fn fee_paid(&self, spent: &Record<N, Plaintext<N>>, change: Option<Record<N, Plaintext<N>>>) {
+        let mut records = self.records.write().unwrap();
+        records.retain(|record| record.nonce() != spent.nonce());
+        records.extend(change);
+    }
+}
+
+/// Returns the microcredits held by a `credits.aleo` record.
+pub fn microcredits<N: Network>(record: &Record<N, Plaintext<N>>) -> Result<u64> {
+    let microcredits = Identifier::from_str("microcredits")?;
+    match record.data().get(&microcredits) {
+        Some(Entry::Private(Plaintext::Literal(Literal::U64(amount), _)))
+            if record.data().len() == 1 =>
+        {
+            Ok(**amount)
+        }
+        _ => Err(Error::Other(
+            "Record is not a credits.aleo record".to_string(),
+        )),
+    }
+}
+
+/// Picks the smallest record holding at least `amount` microcredits.
+///
+/// Records among the call's `inputs` are skipped, so the fee never spends a record the call spends.
+pub fn select_fee_record<N: Network>(
+    records: Vec<Record<N, Plaintext<N>>>,
+    amount: u64,
+    inputs: &[Value<N>],
+) -> Result<Record<N, Plaintext<N>>> {
+    let spent_by_call = |record: &Record<N, Plaintext<N>>| {
+        inputs
+            .iter()
+            .any(|input| matches!(input, Value::Record(input) if input.nonce() == record.nonce()))
+    };
+    records
+        .into_iter()
+        .filter(|record| !spent_by_call(record))
+        .filter_map(|record| {
+            microcredits(&record)
+                .ok()
+                .filter(|balance| *balance >= amount)
+                .map(|balance| (balance, record))
+        })
+        .min_by_key(|(balance, _)| *balance)
+        .map(|(_, record)| record)
+        .ok_or_else(|| {
+            Error::Other(format!(
+                "No credits record holds the fee of {amount} microcredits"
+            ))
+        })
+}
+
+/// Returns the change record of a private fee, if it is owned by `account`.
+pub(crate) fn fee_change<N: Network>(
+    transaction: &Transaction<N>,
+    account: &Account<N>,
+) -> Option<Record<N, Plaintext<N>>> {
+    transaction
+        .transitions()
+        .filter(|transition| transition.is_fee_private())
+        .flat_map(|transition| transition.outputs().iter())
+        .find_map(|output| match output {
+            Output::Record(_, _, Some(record), _) if record.is_owner(account.view_key()) => {
+                record.decrypt(account.view_key()).ok()
+            }
+            _ => None,
+        })
+}
+
+/// The public `credits.aleo` balance of `address` in `vm`, or `None` if it can't be read.
+pub(crate) fn public_balance<N: Network, M: VMManager<N>>(
+    vm: &M,
+    address: &Address<N>,
+) -> Option<u64> {
+    let credits = ProgramID::from_str("credits.aleo").ok()?;
+    let account = Identifier::from_str("account").ok()?;
+    match vm.mapping_value(&credits, &account, &address.to_value()) {
+        Ok(Some(balance)) => Some(u64::from_value(balance)),
+        Ok(None) => Some(0),
+        Err(_) => None,
+    }
+}
+
+/// How far the public balance of `payer` dropped since it was `before`.
+///
+/// Wrappers around a `VMManager` only see mappings, so this is how they measure fees.
+pub(crate) fn balance_drop<N: Network, M: VMManager<N>>(
+    vm: &M,
+    before: Option<u64>,
+    payer: &Address<N>,
+) -> Option<u64> {
+    before?.checked_sub(public_balance(vm, payer)?)
+}
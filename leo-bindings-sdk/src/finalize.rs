@@ -0,0 +1,176 @@
+use crate::error::{Error, Result};
+use crate::simulation::MappingKeys;
+use snarkvm::ledger::block::{Output, Transition};
+use snarkvm::ledger::store::helpers::memory::ConsensusMemory;
+use snarkvm::prelude::*;
+use snarkvm::synthesizer::VM;
+use snarkvm::synthesizer::program::{
+    CallOperator, Command, FinalizeOperation, Operand, StackTrait,
+};
+
+/// A mapping entry that a finalize block reads or writes.
+pub(crate) struct MappingAccess {
+    /// The program and function whose finalize accesses the mapping.
+    pub(crate) caller: (ProgramID<TestnetV0>, Identifier<TestnetV0>),
+    pub(crate) program_id: ProgramID<TestnetV0>,
+    pub(crate) mapping_name: Identifier<TestnetV0>,
+    /// The key, unless it is computed inside finalize.
+    pub(crate) key: Option<Plaintext<TestnetV0>>,
+    pub(crate) write: bool,
+}
+
+/// The mapping entries the finalize blocks of `transitions` access.
+///
+/// Keys are taken from the `get`, `get.or_use`, `contains`, `set` and `remove` commands,
+/// and are only known when they are a literal or a finalize input.
+pub(crate) fn mapping_accesses<'a>(
+    vm: &VM<TestnetV0, ConsensusMemory<TestnetV0>>,
+    transitions: impl IntoIterator<Item = &'a Transition<TestnetV0>>,
+) -> Result<Vec<MappingAccess>> {
+    let mut accesses = Vec::new();
+    for transition in transitions {
+        for output in transition.outputs() {
+            if let Output::Future(_, Some(future)) = output {
+                future_accesses(vm, future, &mut accesses)?;
+            }
+        }
+    }
+    Ok(accesses)
+}
+
+fn future_accesses(
+    vm: &VM<TestnetV0, ConsensusMemory<TestnetV0>>,
+    future: &Future<TestnetV0>,
+    accesses: &mut Vec<MappingAccess>,
+) -> Result<()> {
+    for argument in future.arguments() {
+        if let Argument::Future(future) = argument {
+            future_accesses(vm, future, accesses)?;
+        }
+    }
+
+    let program_id = *future.program_id();
+    let function_name = *future.function_name();
+    let stack = vm
+        .process()
+        .get_stack(program_id)
+        .map_err(|e| Error::Other(format!("get_stack({program_id}): {e}")))?;
+    let function = stack
+        .program()
+        .get_function_ref(&function_name)
+        .map_err(|e| Error::Other(format!("{program_id}/{function_name}: {e}")))?;
+    let Some(finalize) = function.finalize_logic() else {
+        return Ok(());
+    };
+
+    for command in finalize.commands() {
+        let (mapping, key, write) = match command {
+            Command::Get(get) => (get.mapping().clone(), get.key(), false),
+            Command::GetOrUse(get_or_use) => {
+                (get_or_use.mapping().clone(), get_or_use.key(), false)
+            }
+            Command::Contains(contains) => (contains.mapping().clone(), contains.key(), false),
+            Command::Set(set) => (CallOperator::Resource(*set.mapping_name()), set.key(), true),
+            Command::Remove(remove) => (
+                CallOperator::Resource(*remove.mapping_name()),
+                remove.key(),
+                true,
+            ),
+            _ => continue,
+        };
+        let (mapping_program, mapping_name) = match mapping {
+            CallOperator::Locator(locator) => (*locator.program_id(), *locator.resource()),
+            CallOperator::Resource(mapping_name) => (program_id, mapping_name),
+        };
+        accesses.push(MappingAccess {
+            caller: (program_id, function_name),
+            program_id: mapping_program,
+            mapping_name,
+            key: finalize_key(future, key),
+            write,
+        });
+    }
+    Ok(())
+}
+
+/// The key `operand` reads in the finalize of `future`, if it is known before finalize runs.
+///
+/// Finalize inputs occupy the first registers, in the order of the future's arguments.
+fn finalize_key(
+    future: &Future<TestnetV0>,
+    operand: &Operand<TestnetV0>,
+) -> Option<Plaintext<TestnetV0>> {
+    match operand {
+        Operand::Literal(literal) => Some(Plaintext::from(literal.clone())),
+        Operand::Register(Register::Locator(index)) => {
+            match future.arguments().get(*index as usize)? {
+                Argument::Plaintext(plaintext) => Some(plaintext.clone()),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// The mapping and key IDs of the entries `operations` insert, update or remove.
+pub(crate) fn written_key_ids(
+    operations: &[FinalizeOperation<TestnetV0>],
+) -> Vec<(Field<TestnetV0>, Field<TestnetV0>)> {
+    operations
+        .iter()
+        .filter_map(|operation| match operation {
+            FinalizeOperation::InsertKeyValue(mapping_id, key_id, _)
+            | FinalizeOperation::UpdateKeyValue(mapping_id, key_id, _)
+            | FinalizeOperation::RemoveKeyValue(mapping_id, key_id) => Some((*mapping_id, *key_id)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The ID a finalize operation gives a mapping, hashed like the finalize store does.
+pub(crate) fn mapping_id(
+    program_id: &ProgramID<TestnetV0>,
+    mapping_name: &Identifier<TestnetV0>,
+) -> Result<Field<TestnetV0>> {
+    let mut preimage = Vec::new();
+    program_id.write_bits_le(&mut preimage);
+    false.write_bits_le(&mut preimage);
+    mapping_name.write_bits_le(&mut preimage);
+    TestnetV0::hash_bhp1024(&preimage).map_err(|e| Error::Other(format!("mapping ID: {e}")))
+}
+
+/// The ID a finalize operation gives a key, hashed like the finalize store does.
+pub(crate) fn key_id(
+    program_id: &ProgramID<TestnetV0>,
+    mapping_name: &Identifier<TestnetV0>,
+    key: &Plaintext<TestnetV0>,
+) -> Result<Field<TestnetV0>> {
+    let mut preimage = Vec::new();
+    program_id.write_bits_le(&mut preimage);
+    false.write_bits_le(&mut preimage);
+    mapping_name.write_bits_le(&mut preimage);
+    false.write_bits_le(&mut preimage);
+    key.write_bits_le(&mut preimage);
+    TestnetV0::hash_bhp1024(&preimage).map_err(|e| Error::Other(format!("key ID: {e}")))
+}
+
+/// Log the writes to `touched` mappings whose key was not found, which are left out.
+///
+/// Writes to other mappings are the fee's, which are left out on purpose.
+pub(crate) fn log_unresolved(
+    touched: &MappingKeys<TestnetV0>,
+    unresolved: &[(Field<TestnetV0>, Field<TestnetV0>)],
+) {
+    for (program_id, mapping_name) in touched.keys() {
+        let Ok(id) = mapping_id(program_id, mapping_name) else {
+            continue;
+        };
+        let count = unresolved
+            .iter()
+            .filter(|(mapping_id, _)| *mapping_id == id)
+            .count();
+        if count > 0 {
+            log::debug!("{count} writes to {program_id}/{mapping_name} with unknown keys left out");
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use crate::config::Client;
+use crate::error::{Error, Result};
+use crate::finalize::{MappingAccess, mapping_accesses};
+use snarkvm::ledger::block::Transaction;
+use snarkvm::ledger::store::FinalizeStore;
+use snarkvm::ledger::store::helpers::memory::{ConsensusMemory, FinalizeMemory};
+use snarkvm::prelude::*;
+use snarkvm::synthesizer::VM;
+use snarkvm::synthesizer::program::{FinalizeStoreTrait, StackTrait};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+type MappingKey = (
+    ProgramID<TestnetV0>,
+    Identifier<TestnetV0>,
+    Plaintext<TestnetV0>,
+);
+
+/// The remote state a forked `LocalVM` reads through to.
+pub(crate) struct Fork {
+    client: Client,
+    height: u32,
+    /// Whether reads after `height` are rejected instead of accepted.
+    pinned: bool,
+    /// Mapping keys that were pulled from the remote or written locally, with the
+    /// remote height a pulled key was read at.
+    pulled: Mutex<HashMap<MappingKey, Option<u32>>>,
+    /// Mappings whose finalize reads with computed keys are served from the local store.
+    trusted: Mutex<HashSet<(ProgramID<TestnetV0>, Identifier<TestnetV0>)>>,
+}
+
+impl Fork {
+    pub(crate) fn new(client: Client, height: u32, pinned: bool) -> Self {
+        Self {
+            client,
+            height,
+            pinned,
+            pulled: Mutex::new(HashMap::new()),
+            trusted: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub(crate) fn client(&self) -> &Client {
+        &self.client
+    }
+
+    pub(crate) fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Copy the fork with its own record of pulled keys, for snapshots.
+    pub(crate) fn snapshot(&self) -> Self {
+        Self {
+            client: self.client.clone(),
+            height: self.height,
+            pinned: self.pinned,
+            pulled: Mutex::new(self.pulled.lock().unwrap().clone()),
+            trusted: Mutex::new(self.trusted.lock().unwrap().clone()),
+        }
+    }
+
+    /// The keys pulled from the remote, with the height each was read at.
+    ///
+    /// An unpinned fork reads every key at the node's latest height, so the heights differ
+    /// once the node moves on while the fork is in use.
+    pub(crate) fn reads(&self) -> Vec<(MappingKey, u32)> {
+        self.pulled
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|(key, height)| Some((key.clone(), (*height)?)))
+            .collect()
+    }
+
+    /// Serve finalize reads of `mapping_name` with computed keys from the local store.
+    pub(crate) fn trust_mapping(
+        &self,
+        program_id: ProgramID<TestnetV0>,
+        mapping_name: Identifier<TestnetV0>,
+    ) {
+        self.trusted
+            .lock()
+            .unwrap()
+            .insert((program_id, mapping_name));
+    }
+
+    /// Stop pulling `key` because it was written locally.
+    pub(crate) fn mark_written(
+        &self,
+        program_id: ProgramID<TestnetV0>,
+        mapping_name: Identifier<TestnetV0>,
+        key: Plaintext<TestnetV0>,
+    ) {
+        self.pulled
+            .lock()
+            .unwrap()
+            .entry((program_id, mapping_name, key))
+            .or_insert(None);
+    }
+
+    /// Copy the remote value of `key` into `finalize_store`, once per key.
+    pub(crate) fn pull_mapping_value(
+        &self,
+        finalize_store: &FinalizeStore<TestnetV0, FinalizeMemory<TestnetV0>>,
+        program_id: ProgramID<TestnetV0>,
+        mapping_name: Identifier<TestnetV0>,
+        key: &Plaintext<TestnetV0>,
+    ) -> Result<()> {
+        let entry = (program_id, mapping_name, key.clone());
+        if self.pulled.lock().unwrap().contains_key(&entry) {
+            return Ok(());
+        }
+        if finalize_store.contains_key_confirmed(program_id, mapping_name, key)? {
+            self.pulled.lock().unwrap().insert(entry, None);
+            return Ok(());
+        }
+
+        let (value, height) = crate::block_on(self.client.mapping_with_height::<TestnetV0>(
+            &program_id.to_string(),
+            &mapping_name.to_string(),
+            &Value::Plaintext(key.clone()),
+        ))?;
+        if self.pinned && height > self.height {
+            return Err(Error::Other(format!(
+                "Fork: {program_id}/{mapping_name}[{key}] can only be read at height {height}, after the pinned height {}",
+                self.height
+            )));
+        }
+        if height != self.height {
+            log::debug!(
+                "🍴 Read {program_id}/{mapping_name}[{key}] at height {height}, forked at {}",
+                self.height
+            );
+        }
+        if let Some(value) = value {
+            finalize_store
+                .update_key_value(program_id, mapping_name, key.clone(), value)
+                .map_err(|e| Error::Other(format!("Failed to store pulled value: {e}")))?;
+        }
+        self.pulled.lock().unwrap().insert(entry, Some(height));
+        Ok(())
+    }
+
+    /// Pull the values `keys` may have in the mappings of `program_id`.
+    ///
+    /// A key is only pulled for mappings whose key type it matches.
+    pub(crate) fn pull_matching_keys(
+        &self,
+        vm: &VM<TestnetV0, ConsensusMemory<TestnetV0>>,
+        program_id: &ProgramID<TestnetV0>,
+        keys: &[&Plaintext<TestnetV0>],
+    ) -> Result<()> {
+        let stack = vm
+            .process()
+            .get_stack(*program_id)
+            .map_err(|e| Error::Other(format!("get_stack({program_id}): {e}")))?;
+        for (mapping_name, mapping) in stack.program().mappings() {
+            for key in keys {
+                if key_matches(key, mapping.key().plaintext_type()) {
+                    self.pull_mapping_value(vm.finalize_store(), *program_id, *mapping_name, key)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Pull the mapping values the finalize blocks of `transaction` read.
+    ///
+    /// Finalize reads go straight to the local store, so the keys are taken from the
+    /// finalize commands, including reads of other programs' mappings. Keys computed
+    /// inside finalize cannot be pulled and fail the call unless their mapping was
+    /// trusted with `trust_mapping`.
+    pub(crate) fn pull_finalize_state(
+        &self,
+        vm: &VM<TestnetV0, ConsensusMemory<TestnetV0>>,
+        transaction: &Transaction<TestnetV0>,
+    ) -> Result<()> {
+        for access in mapping_accesses(vm, transaction.transitions())? {
+            let MappingAccess {
+                caller: (program_id, function_name),
+                program_id: mapping_program,
+                mapping_name,
+                key,
+                write,
+            } = access;
+            if write {
+                continue;
+            }
+            match key {
+                Some(key) => self.pull_mapping_value(
+                    vm.finalize_store(),
+                    mapping_program,
+                    mapping_name,
+                    &key,
+                )?,
+                None if self
+                    .trusted
+                    .lock()
+                    .unwrap()
+                    .contains(&(mapping_program, mapping_name)) => {}
+                None => {
+                    return Err(Error::Other(format!(
+                        "Fork: the finalize of {program_id}/{function_name} reads {mapping_program}/{mapping_name} with a key computed in finalize, which cannot be pulled; seed the key with mapping_value or set_mapping_value and call LocalVM::trust_fork_mapping"
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn key_matches(key: &Plaintext<TestnetV0>, key_type: &PlaintextType<TestnetV0>) -> bool {
+    match (key, key_type) {
+        (Plaintext::Literal(literal, _), PlaintextType::Literal(literal_type)) => {
+            literal.to_type() == *literal_type
+        }
+        (Plaintext::Literal(..), _) | (_, PlaintextType::Literal(_)) => false,
+        _ => true,
+    }
+}
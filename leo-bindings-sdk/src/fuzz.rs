@@ -0,0 +1,478 @@
+use crate::account::Account;
+use crate::address::Address;
+use crate::output::CallOutput;
+use crate::vm_manager::{LocalVM, LocalVMSnapshot};
+use proptest::prelude::{Just, Strategy, any, prop_oneof};
+use proptest::sample::select;
+use proptest::strategy::{BoxedStrategy, Union};
+use proptest::test_runner::{Config, RngAlgorithm, TestCaseError, TestError, TestRng, TestRunner};
+use rand::Rng;
+use rand_chacha::ChaChaRng;
+use rand_chacha::rand_core::SeedableRng;
+use snarkvm::prelude::{Field, Group, Identifier, Network, One, Scalar, Signature, Uniform, Zero};
+use std::cell::RefCell;
+use std::fmt;
+use std::str::FromStr;
+
+/// Environment variable fixing the fuzzer seed, to replay a reported failure.
+pub const FUZZ_SEED_ENV: &str = "LEO_BINDINGS_FUZZ_SEED";
+
+/// Replays spent on shrinking a failing call sequence before reporting it.
+const MAX_SHRINK_REPLAYS: u32 = 256;
+
+/// The accounts the fuzzer calls from, which also sign and receive generated values.
+#[derive(Clone, Debug)]
+pub struct Actors<N: Network> {
+    accounts: Vec<Account<N>>,
+}
+
+impl<N: Network> Actors<N> {
+    pub fn new(accounts: &[Account<N>]) -> Self {
+        assert!(!accounts.is_empty(), "fuzzing needs at least one actor");
+        Self {
+            accounts: accounts.to_vec(),
+        }
+    }
+
+    pub fn accounts(&self) -> &[Account<N>] {
+        &self.accounts
+    }
+
+    /// One of the actors, shrinking toward the first.
+    pub fn account(&self) -> BoxedStrategy<Account<N>> {
+        select(self.accounts.clone()).boxed()
+    }
+}
+
+/// A value with a proptest strategy, generated for every ABI type.
+///
+/// Addresses and signatures come from the actors, so strategies are built from them.
+pub trait Arbitrary<N: Network>: Sized + Clone + fmt::Debug + 'static {
+    fn strategy(actors: &Actors<N>) -> BoxedStrategy<Self>;
+}
+
+macro_rules! arbitrary_int {
+    ($($ty:ty),*) => {$(
+        /// Edge values and small numbers as often as uniformly random ones.
+        impl<N: Network> Arbitrary<N> for $ty {
+            fn strategy(_actors: &Actors<N>) -> BoxedStrategy<Self> {
+                prop_oneof![
+                    1 => Just(0),
+                    1 => Just(1),
+                    1 => Just(<$ty>::MAX),
+                    1 => Just(<$ty>::MIN),
+                    2 => (0 as $ty)..100,
+                    2 => any::<$ty>(),
+                ]
+                .boxed()
+            }
+        }
+    )*};
+}
+
+arbitrary_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl<N: Network> Arbitrary<N> for bool {
+    fn strategy(_actors: &Actors<N>) -> BoxedStrategy<Self> {
+        any::<bool>().boxed()
+    }
+}
+
+impl<N: Network> Arbitrary<N> for Address<N> {
+    fn strategy(actors: &Actors<N>) -> BoxedStrategy<Self> {
+        actors
+            .account()
+            .prop_map(|account| account.address())
+            .boxed()
+    }
+}
+
+impl<N: Network> Arbitrary<N> for Field<N> {
+    fn strategy(_actors: &Actors<N>) -> BoxedStrategy<Self> {
+        prop_oneof![
+            Just(Field::zero()),
+            Just(Field::one()),
+            (0u64..100).prop_map(Field::from_u64),
+            any::<[u8; 32]>().prop_map(|seed| Field::rand(&mut ChaChaRng::from_seed(seed))),
+        ]
+        .boxed()
+    }
+}
+
+impl<N: Network> Arbitrary<N> for Scalar<N> {
+    fn strategy(_actors: &Actors<N>) -> BoxedStrategy<Self> {
+        prop_oneof![
+            Just(Scalar::zero()),
+            Just(Scalar::one()),
+            any::<[u8; 32]>().prop_map(|seed| Scalar::rand(&mut ChaChaRng::from_seed(seed))),
+        ]
+        .boxed()
+    }
+}
+
+impl<N: Network> Arbitrary<N> for Group<N> {
+    fn strategy(_actors: &Actors<N>) -> BoxedStrategy<Self> {
+        prop_oneof![
+            Just(Group::zero()),
+            Just(Group::generator()),
+            any::<[u8; 32]>().prop_map(|seed| Group::rand(&mut ChaChaRng::from_seed(seed))),
+        ]
+        .boxed()
+    }
+}
+
+impl<N: Network> Arbitrary<N> for Identifier<N> {
+    fn strategy(_actors: &Actors<N>) -> BoxedStrategy<Self> {
+        (0u32..1000)
+            .prop_map(|i| Identifier::from_str(&format!("id{i}")).expect("invalid identifier"))
+            .boxed()
+    }
+}
+
+/// A valid signature by one of the actors over a random message.
+impl<N: Network> Arbitrary<N> for Signature<N> {
+    fn strategy(actors: &Actors<N>) -> BoxedStrategy<Self> {
+        (actors.account(), any::<[u8; 32]>())
+            .prop_map(|(account, seed)| {
+                let rng = &mut ChaChaRng::from_seed(seed);
+                let message = Field::rand(rng);
+                account
+                    .private_key()
+                    .sign(&[message], rng)
+                    .expect("failed to sign a random message")
+            })
+            .boxed()
+    }
+}
+
+impl<N: Network, T: Arbitrary<N>> Arbitrary<N> for Option<T> {
+    fn strategy(actors: &Actors<N>) -> BoxedStrategy<Self> {
+        proptest::option::weighted(0.75, T::strategy(actors)).boxed()
+    }
+}
+
+impl<N: Network, T: Arbitrary<N>, const L: usize> Arbitrary<N> for [T; L] {
+    fn strategy(actors: &Actors<N>) -> BoxedStrategy<Self> {
+        proptest::array::uniform(T::strategy(actors)).boxed()
+    }
+}
+
+/// The call enum of a program's bindings.
+pub trait FuzzCall<N: Network>: Clone + fmt::Debug + fmt::Display + 'static {
+    /// A strategy for every function whose inputs are all plaintext, by function name.
+    fn strategies(actors: &Actors<N>) -> Vec<(&'static str, BoxedStrategy<Self>)>;
+}
+
+/// A call made by the fuzzer.
+#[derive(Clone, Debug)]
+pub struct FuzzStep<N: Network, C> {
+    pub actor: Account<N>,
+    pub call: C,
+    /// The error if the call failed, which does not stop the sequence.
+    pub error: Option<String>,
+}
+
+/// A call sequence that breaks an invariant, shrunk as far as the replay budget allowed.
+#[derive(Clone, Debug)]
+pub struct FuzzFailure<N: Network, C> {
+    pub invariant: String,
+    pub seed: u64,
+    /// The calls up to and including the one after which the invariant broke.
+    pub steps: Vec<FuzzStep<N, C>>,
+}
+
+impl<N: Network, C: fmt::Display> fmt::Display for FuzzFailure<N, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "invariant `{}` broken after {} calls (seed {}):",
+            self.invariant,
+            self.steps.len(),
+            self.seed
+        )?;
+        for (index, step) in self.steps.iter().enumerate() {
+            write!(f, "  {}. {} {}", index + 1, step.actor.address(), step.call)?;
+            match &step.error {
+                Some(error) => writeln!(f, " failed: {error}")?,
+                None => writeln!(f)?,
+            }
+        }
+        write!(f, "replay with {FUZZ_SEED_ENV}={}", self.seed)
+    }
+}
+
+type Invariant<P> = (String, Box<dyn Fn(&P) -> bool>);
+
+type Execute<N, C, P> = fn(&P, &Account<N>, &C) -> Result<Vec<CallOutput<N>>, anyhow::Error>;
+
+type CallStrategy<N, C> = Box<dyn Fn(&Actors<N>) -> BoxedStrategy<C>>;
+
+/// Runs random call sequences from a `LocalVM` snapshot and checks invariants after every call.
+///
+/// `P` is the program bindings, rebuilt on a fresh copy of the snapshot for every sequence.
+/// Sequences are generated and shrunk by proptest, from one `u64` seed so that a reported
+/// failure replays through `LEO_BINDINGS_FUZZ_SEED`.
+pub struct Fuzzer<N: Network, C, P> {
+    snapshot: LocalVMSnapshot,
+    rebind: Box<dyn Fn(LocalVM) -> P>,
+    execute: Execute<N, C, P>,
+    actors: Actors<N>,
+    strategies: Vec<(String, CallStrategy<N, C>)>,
+    invariants: Vec<Invariant<P>>,
+    runs: u32,
+    depth: usize,
+    seed: u64,
+}
+
+impl<N: Network, C: FuzzCall<N>, P> Fuzzer<N, C, P> {
+    /// Fuzz from the current state of `vm`, seeded from `LEO_BINDINGS_FUZZ_SEED` or at random.
+    pub fn new(
+        vm: &LocalVM,
+        actors: &[Account<N>],
+        rebind: impl Fn(LocalVM) -> P + 'static,
+        execute: Execute<N, C, P>,
+    ) -> Self {
+        let seed = std::env::var(FUZZ_SEED_ENV)
+            .ok()
+            .and_then(|seed| seed.trim().parse().ok())
+            .unwrap_or_else(|| rand::rng().next_u64());
+        Self {
+            snapshot: vm.snapshot(),
+            rebind: Box::new(rebind),
+            execute,
+            actors: Actors::new(actors),
+            strategies: Vec::new(),
+            invariants: Vec::new(),
+            runs: 32,
+            depth: 16,
+            seed,
+        }
+    }
+
+    /// The number of call sequences, 32 by default.
+    pub fn runs(mut self, runs: u32) -> Self {
+        self.runs = runs;
+        self
+    }
+
+    /// The maximum number of calls per sequence, 16 by default.
+    pub fn depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Generate calls to `function` from `strategy` instead of from its input types.
+    ///
+    /// This also fuzzes functions with record inputs, which have no generated strategy.
+    pub fn strategy<S: Strategy<Value = C> + 'static>(
+        mut self,
+        function: &str,
+        strategy: impl Fn(&Actors<N>) -> S + 'static,
+    ) -> Self {
+        self.strategies.retain(|(name, _)| name != function);
+        self.strategies.push((
+            function.to_string(),
+            Box::new(move |actors| strategy(actors).boxed()),
+        ));
+        self
+    }
+
+    /// Require `holds` to be true before the first call and after every call.
+    pub fn invariant(mut self, name: &str, holds: impl Fn(&P) -> bool + 'static) -> Self {
+        self.invariants.push((name.to_string(), Box::new(holds)));
+        self
+    }
+
+    /// Panic with the shrunk transcript if an invariant breaks.
+    #[track_caller]
+    pub fn run(&self) {
+        if let Some(failure) = self.check() {
+            panic!("{failure}");
+        }
+    }
+
+    /// The first invariant failure, shrunk, or `None` if every sequence kept the invariants.
+    pub fn check(&self) -> Option<FuzzFailure<N, C>> {
+        let sequences = proptest::collection::vec(
+            (self.actors.account(), self.call_strategy()),
+            1..=self.depth.max(1),
+        );
+        let config = Config {
+            cases: self.runs,
+            max_shrink_iters: MAX_SHRINK_REPLAYS,
+            failure_persistence: None,
+            ..Config::default()
+        };
+        let mut seed = [0u8; 32];
+        seed[..8].copy_from_slice(&self.seed.to_le_bytes());
+        let mut runner =
+            TestRunner::new_with_rng(config, TestRng::from_seed(RngAlgorithm::ChaCha, &seed));
+
+        let replays = RefCell::new(Replays::default());
+        let result = runner.run(&sequences, |calls| {
+            replays
+                .borrow_mut()
+                .outcome(self, &calls)
+                .map_err(TestCaseError::fail)
+        });
+        let replays = replays.into_inner();
+        let calls = match result {
+            Ok(()) => {
+                log::info!(
+                    "🎲 {} runs of up to {} calls kept every invariant (seed {})",
+                    self.runs,
+                    self.depth,
+                    self.seed
+                );
+                return None;
+            }
+            Err(TestError::Fail(_, calls)) => calls,
+            Err(TestError::Abort(reason)) => panic!("fuzzing aborted: {reason}"),
+        };
+        log::info!(
+            "🎲 shrunk to {} calls with {} replays",
+            calls.len(),
+            replays.count
+        );
+        let (invariant, steps) = match replays.steps(&calls) {
+            Some(steps) => (
+                replays.invariant.clone().expect("no invariant broke"),
+                steps,
+            ),
+            None => match self.replay(&calls) {
+                (steps, Some(invariant)) => (invariant, steps),
+                (_, None) => unreachable!("the shrunk sequence keeps every invariant"),
+            },
+        };
+        Some(FuzzFailure {
+            invariant,
+            seed: self.seed,
+            steps,
+        })
+    }
+
+    /// One generated call to any function, with overrides replacing the generated strategies.
+    fn call_strategy(&self) -> Union<BoxedStrategy<C>> {
+        let generated = C::strategies(&self.actors)
+            .into_iter()
+            .filter(|(function, _)| !self.strategies.iter().any(|(name, _)| name == function))
+            .map(|(_, strategy)| strategy);
+        let overrides = self
+            .strategies
+            .iter()
+            .map(|(_, strategy)| strategy(&self.actors));
+        let strategies: Vec<_> = generated.chain(overrides).collect();
+        assert!(!strategies.is_empty(), "no function to fuzz");
+        Union::new(strategies)
+    }
+
+    /// Run `calls` on a fresh copy of the snapshot, stopping at the first broken invariant.
+    fn replay(&self, calls: &[(Account<N>, C)]) -> (Vec<FuzzStep<N, C>>, Option<String>) {
+        let program = (self.rebind)(self.snapshot.restore());
+        let mut steps = Vec::new();
+        if let Some(invariant) = self.broken(&program) {
+            return (steps, Some(invariant));
+        }
+        for (actor, call) in calls {
+            let error = (self.execute)(&program, actor, call).err();
+            steps.push(FuzzStep {
+                actor: actor.clone(),
+                call: call.clone(),
+                error: error.map(|e| e.to_string()),
+            });
+            if let Some(invariant) = self.broken(&program) {
+                return (steps, Some(invariant));
+            }
+        }
+        (steps, None)
+    }
+
+    fn broken(&self, program: &P) -> Option<String> {
+        self.invariants
+            .iter()
+            .find(|(_, holds)| !holds(program))
+            .map(|(name, _)| name.clone())
+    }
+}
+
+/// Replayed call sequences, so that shrinking only restores the snapshot for new candidates.
+///
+/// Calls are deterministic from the snapshot, so a candidate extending a sequence that broke
+/// the invariant breaks it too, and a prefix of a sequence that kept every invariant keeps them.
+struct Replays<N: Network, C> {
+    /// The invariant being shrunk, which the first failing sequence broke.
+    invariant: Option<String>,
+    /// Calls up to the one that broke `invariant`, with their steps.
+    broken: Vec<(Vec<String>, Vec<FuzzStep<N, C>>)>,
+    kept: Vec<Vec<String>>,
+    count: usize,
+}
+
+impl<N: Network, C> Default for Replays<N, C> {
+    fn default() -> Self {
+        Self {
+            invariant: None,
+            broken: Vec::new(),
+            kept: Vec::new(),
+            count: 0,
+        }
+    }
+}
+
+impl<N: Network, C: FuzzCall<N>> Replays<N, C> {
+    fn keys(calls: &[(Account<N>, C)]) -> Vec<String> {
+        calls
+            .iter()
+            .map(|(actor, call)| format!("{} {call}", actor.address()))
+            .collect()
+    }
+
+    /// The invariant `calls` break, replaying them only if no earlier replay decides it.
+    ///
+    /// Breaking a different invariant than the one being shrunk counts as keeping it.
+    fn outcome<P>(
+        &mut self,
+        fuzzer: &Fuzzer<N, C, P>,
+        calls: &[(Account<N>, C)],
+    ) -> Result<(), String> {
+        let keys = Self::keys(calls);
+        if self
+            .broken
+            .iter()
+            .any(|(prefix, _)| keys.starts_with(prefix))
+        {
+            return Err(self.invariant.clone().expect("no invariant broke"));
+        }
+        if self.kept.iter().any(|kept| kept.starts_with(&keys)) {
+            return Ok(());
+        }
+        self.count += 1;
+        match fuzzer.replay(calls) {
+            (steps, Some(invariant)) => {
+                if *self.invariant.get_or_insert_with(|| invariant.clone()) != invariant {
+                    return Ok(());
+                }
+                self.broken.push((keys[..steps.len()].to_vec(), steps));
+                Err(invariant)
+            }
+            (_, None) => {
+                self.kept.push(keys);
+                Ok(())
+            }
+        }
+    }
+
+    /// The steps of the replay `calls` broke the invariant in.
+    fn steps(&self, calls: &[(Account<N>, C)]) -> Option<Vec<FuzzStep<N, C>>> {
+        let keys = Self::keys(calls);
+        self.broken
+            .iter()
+            .find(|(prefix, _)| keys.starts_with(prefix))
+            .map(|(_, steps)| steps.clone())
+    }
+}
@@ -0,0 +1,224 @@
+use crate::error::{Error, Result};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+
+/// Environment variable naming the checked-in gas snapshot file.
+pub const GAS_SNAPSHOT_ENV: &str = "LEO_BINDINGS_GAS_SNAPSHOT";
+
+/// Environment variable enabling check mode, holding the tolerated cost increase in percent.
+pub const GAS_SNAPSHOT_CHECK_ENV: &str = "LEO_BINDINGS_GAS_SNAPSHOT_CHECK";
+
+/// Base fee in microcredits per deployed program and per `program/function`.
+///
+/// The file holds one `<call> <microcredits>` line per entry, sorted so it diffs well.
+/// Recorded calls are prefixed with the VM that measured them, such as `local:token.aleo`
+/// or `network:token.aleo/mint_public`, because proofless `LocalVM` transactions cost less.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GasSnapshot {
+    costs: BTreeMap<String, u64>,
+}
+
+impl GasSnapshot {
+    /// Read a snapshot file, or an empty snapshot if it does not exist yet.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        match std::fs::read_to_string(path) {
+            Ok(contents) => contents.parse(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(Error::Other(format!(
+                "Failed to read gas snapshot {}: {e}",
+                path.display()
+            ))),
+        }
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_string()).map_err(|e| {
+            Error::Other(format!(
+                "Failed to write gas snapshot {}: {e}",
+                path.display()
+            ))
+        })
+    }
+
+    pub fn get(&self, call: &str) -> Option<u64> {
+        self.costs.get(call).copied()
+    }
+
+    pub fn insert(&mut self, call: impl Into<String>, cost: u64) {
+        self.costs.insert(call.into(), cost);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.costs.iter().map(|(call, cost)| (call.as_str(), *cost))
+    }
+
+    /// Compare `cost` against the snapshot.
+    ///
+    /// Increases beyond `tolerance` percent are errors, smaller increases and unknown calls are logged as warnings.
+    pub fn check(&self, call: &str, cost: u64, tolerance: f64) -> Result<()> {
+        let Some(snapshot) = self.get(call) else {
+            log::warn!("⛽ {call} is not in the gas snapshot (cost {cost})");
+            return Ok(());
+        };
+        if cost > snapshot {
+            let increase = (cost - snapshot) as f64 * 100.0 / snapshot.max(1) as f64;
+            if increase > tolerance {
+                return Err(Error::CostIncreased {
+                    call: call.to_string(),
+                    snapshot,
+                    cost,
+                });
+            }
+            log::warn!("⛽ {call} cost rose from {snapshot} to {cost} (+{increase:.2}%)");
+        } else if cost < snapshot {
+            log::info!(
+                "⛽ {call} cost fell from {snapshot} to {cost}, consider updating the snapshot"
+            );
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for GasSnapshot {
+    type Err = Error;
+
+    fn from_str(contents: &str) -> Result<Self> {
+        let mut snapshot = Self::default();
+        for line in contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+        {
+            let (call, cost) = line
+                .rsplit_once(' ')
+                .and_then(|(call, cost)| Some((call.trim(), cost.parse().ok()?)))
+                .ok_or_else(|| Error::Other(format!("Invalid gas snapshot line: {line}")))?;
+            snapshot.insert(call, cost);
+        }
+        Ok(snapshot)
+    }
+}
+
+impl fmt::Display for GasSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (call, cost) in &self.costs {
+            writeln!(f, "{call} {cost}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The kind of VM a cost was measured on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Backend {
+    Local,
+    Network,
+}
+
+impl Backend {
+    fn key(self, call: &str) -> String {
+        match self {
+            Backend::Local => format!("local:{call}"),
+            Backend::Network => format!("network:{call}"),
+        }
+    }
+}
+
+enum Mode {
+    /// Keep the highest cost of every call made by this process over the file's entries,
+    /// until they are written with `flush_gas_snapshot`.
+    Record {
+        path: PathBuf,
+        snapshot: GasSnapshot,
+        observed: BTreeMap<String, u64>,
+    },
+    Check {
+        snapshot: GasSnapshot,
+        tolerance: f64,
+    },
+    /// The environment is misconfigured, so every checked call fails.
+    Invalid(String),
+}
+
+static MODE: LazyLock<Mutex<Option<Mode>>> = LazyLock::new(|| Mutex::new(mode_from_env()));
+
+fn mode_from_env() -> Option<Mode> {
+    let path = PathBuf::from(std::env::var(GAS_SNAPSHOT_ENV).ok()?);
+    let snapshot = GasSnapshot::read(&path)
+        .inspect_err(|e| log::warn!("{e}"))
+        .unwrap_or_default();
+    Some(match std::env::var(GAS_SNAPSHOT_CHECK_ENV) {
+        Ok(tolerance) => match tolerance.trim().parse::<f64>() {
+            Ok(tolerance) if tolerance.is_finite() && tolerance >= 0.0 => Mode::Check {
+                snapshot,
+                tolerance,
+            },
+            _ => Mode::Invalid(format!(
+                "{GAS_SNAPSHOT_CHECK_ENV} must be a tolerance in percent such as 0 or 5, got '{tolerance}'"
+            )),
+        },
+        Err(_) => Mode::Record {
+            path,
+            snapshot,
+            observed: BTreeMap::new(),
+        },
+    })
+}
+
+/// Write the costs recorded so far to the `LEO_BINDINGS_GAS_SNAPSHOT` file.
+///
+/// Does nothing unless the snapshot is being recorded. Call it once the calls have run,
+/// or hold a [`GasSnapshotGuard`] for the duration of the test.
+pub fn flush_gas_snapshot() -> Result<()> {
+    let Ok(mode) = MODE.lock() else {
+        return Err(Error::Other("Gas snapshot state is poisoned".to_string()));
+    };
+    match mode.as_ref() {
+        Some(Mode::Record { path, snapshot, .. }) => snapshot.write(path),
+        _ => Ok(()),
+    }
+}
+
+/// Calls `flush_gas_snapshot` when dropped, logging a failure to write the file.
+#[derive(Debug, Default)]
+#[must_use = "the snapshot is written when the guard is dropped"]
+pub struct GasSnapshotGuard;
+
+impl Drop for GasSnapshotGuard {
+    fn drop(&mut self) {
+        if let Err(e) = flush_gas_snapshot() {
+            log::warn!("{e}");
+        }
+    }
+}
+
+/// Check the cost of `call` against the snapshot if `LEO_BINDINGS_GAS_SNAPSHOT_CHECK` is set.
+///
+/// Called before the transaction is broadcast, so a regression never fails a call that is already on chain.
+pub(crate) fn check(backend: Backend, call: &str, cost: u64) -> Result<()> {
+    match MODE.lock().unwrap().as_ref() {
+        Some(Mode::Invalid(message)) => Err(Error::Config(message.clone())),
+        Some(Mode::Check {
+            snapshot,
+            tolerance,
+        }) => snapshot.check(&backend.key(call), cost, *tolerance),
+        _ => Ok(()),
+    }
+}
+
+/// Record the cost of `call` if the snapshot is being recorded.
+pub(crate) fn record(backend: Backend, call: &str, cost: u64) {
+    if let Some(Mode::Record {
+        snapshot, observed, ..
+    }) = MODE.lock().unwrap().as_mut()
+    {
+        let call = backend.key(call);
+        let observed = observed.entry(call.clone()).or_default();
+        *observed = (*observed).max(cost);
+        snapshot.insert(call, *observed);
+    }
+}
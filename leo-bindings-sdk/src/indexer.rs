@@ -0,0 +1,423 @@
+use crate::config::Client;
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use snarkvm::ledger::block::{Block, Input, Output, Transition};
+use snarkvm::prelude::{Argument, Identifier, Network, ProgramID, Value};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How many blocks must be built on top of a block before it is followed.
+const DEFAULT_CONFIRMATIONS: u32 = 2;
+
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How many blocks one `poll` fetches at most, so a follower far behind catches up in steps.
+const DEFAULT_MAX_BLOCKS_PER_POLL: u32 = 100;
+
+/// The last block a follower has processed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Checkpoint<N: Network> {
+    pub height: u32,
+    pub hash: N::BlockHash,
+}
+
+/// Persists the checkpoint of an `Indexer` so it resumes after a restart.
+pub trait CheckpointStore<N: Network> {
+    fn load(&self) -> Result<Option<Checkpoint<N>>>;
+    fn save(&self, checkpoint: &Checkpoint<N>) -> Result<()>;
+}
+
+/// A checkpoint store that only lives as long as the process.
+#[derive(Debug)]
+pub struct MemoryCheckpointStore<N: Network> {
+    checkpoint: Mutex<Option<Checkpoint<N>>>,
+}
+
+impl<N: Network> Default for MemoryCheckpointStore<N> {
+    fn default() -> Self {
+        Self {
+            checkpoint: Mutex::new(None),
+        }
+    }
+}
+
+impl<N: Network> CheckpointStore<N> for MemoryCheckpointStore<N> {
+    fn load(&self) -> Result<Option<Checkpoint<N>>> {
+        Ok(self.checkpoint.lock().unwrap().clone())
+    }
+
+    fn save(&self, checkpoint: &Checkpoint<N>) -> Result<()> {
+        *self.checkpoint.lock().unwrap() = Some(checkpoint.clone());
+        Ok(())
+    }
+}
+
+/// A checkpoint store backed by a JSON file.
+#[derive(Clone, Debug)]
+pub struct FileCheckpointStore {
+    path: PathBuf,
+}
+
+impl FileCheckpointStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl<N: Network> CheckpointStore<N> for FileCheckpointStore {
+    fn load(&self) -> Result<Option<Checkpoint<N>>> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(json) => Ok(Some(serde_json::from_str(&json)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(Error::Other(format!(
+                "Failed to read checkpoint {}: {e}",
+                self.path.display()
+            ))),
+        }
+    }
+
+    fn save(&self, checkpoint: &Checkpoint<N>) -> Result<()> {
+        // Write to a temporary file first so a crash never leaves a truncated checkpoint.
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_string(checkpoint)?)
+            .and_then(|_| std::fs::rename(&tmp, &self.path))
+            .map_err(|e| {
+                Error::Other(format!(
+                    "Failed to write checkpoint {}: {e}",
+                    self.path.display()
+                ))
+            })
+    }
+}
+
+/// Follows the chain block by block, once blocks are deep enough not to be reorged.
+pub struct BlockFollower<N: Network> {
+    client: Client,
+    next_height: u32,
+    last_hash: Option<N::BlockHash>,
+    confirmations: u32,
+    poll_interval: Duration,
+    max_blocks_per_poll: u32,
+}
+
+impl<N: Network> BlockFollower<N> {
+    /// Follow blocks starting at `start_height`.
+    pub fn new(client: Client, start_height: u32) -> Self {
+        Self {
+            client,
+            next_height: start_height,
+            last_hash: None,
+            confirmations: DEFAULT_CONFIRMATIONS,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            max_blocks_per_poll: DEFAULT_MAX_BLOCKS_PER_POLL,
+        }
+    }
+
+    /// Follow blocks after `checkpoint`, checking that they extend it.
+    pub fn resume(client: Client, checkpoint: &Checkpoint<N>) -> Self {
+        Self {
+            last_hash: Some(checkpoint.hash),
+            ..Self::new(client, checkpoint.height + 1)
+        }
+    }
+
+    /// Only follow blocks with at least `confirmations` blocks on top of them.
+    pub fn confirmations(mut self, confirmations: u32) -> Self {
+        self.confirmations = confirmations;
+        self
+    }
+
+    /// How long `next_block` sleeps while waiting for a new block.
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Fetch at most `max` blocks per `poll`, 100 by default.
+    pub fn max_blocks_per_poll(mut self, max: u32) -> Self {
+        self.max_blocks_per_poll = max.max(1);
+        self
+    }
+
+    /// The last block followed, if any.
+    pub fn checkpoint(&self) -> Option<Checkpoint<N>> {
+        self.last_hash.map(|hash| Checkpoint {
+            height: self.next_height - 1,
+            hash,
+        })
+    }
+
+    /// Fetch the confirmed blocks not yet followed, up to the per-poll maximum.
+    ///
+    /// The follower only advances if the whole batch extends the last block followed.
+    pub async fn poll(&mut self) -> Result<Vec<Block<N>>> {
+        let height = self.client.height::<N>().await?;
+        let Some(confirmed) = height.checked_sub(self.confirmations) else {
+            return Ok(Vec::new());
+        };
+        if confirmed < self.next_height {
+            return Ok(Vec::new());
+        }
+
+        let last = self
+            .next_height
+            .saturating_add(self.max_blocks_per_poll - 1);
+        let end = confirmed.min(last);
+        let blocks = self.client.blocks::<N>(self.next_height, end + 1).await?;
+        let (mut next_height, mut last_hash) = (self.next_height, self.last_hash);
+        for block in &blocks {
+            self.check_extends(next_height, last_hash, block)?;
+            next_height += 1;
+            last_hash = Some(block.hash());
+        }
+        self.next_height = next_height;
+        self.last_hash = last_hash;
+        Ok(blocks)
+    }
+
+    /// Wait for the next confirmed block.
+    pub async fn next_block(&mut self) -> Result<Block<N>> {
+        loop {
+            let height = self.client.height::<N>().await?;
+            if height >= self.next_height.saturating_add(self.confirmations) {
+                let block = self.client.block::<N>(self.next_height).await?;
+                self.check_extends(self.next_height, self.last_hash, &block)?;
+                self.last_hash = Some(block.hash());
+                self.next_height += 1;
+                return Ok(block);
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Check that `block` is at `height` and extends the block with `last_hash`.
+    fn check_extends(
+        &self,
+        height: u32,
+        last_hash: Option<N::BlockHash>,
+        block: &Block<N>,
+    ) -> Result<()> {
+        if block.height() != height {
+            return Err(Error::Other(format!(
+                "Expected block {height}, got {}",
+                block.height()
+            )));
+        }
+        if let Some(last_hash) = last_hash
+            && block.previous_hash() != last_hash
+        {
+            return Err(Error::Other(format!(
+                "Block {} does not extend {last_hash}, the chain reorganized deeper than {} confirmations",
+                block.height(),
+                self.confirmations
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// A transition of an accepted transaction, with its public values.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ProgramCall<N: Network> {
+    pub program_id: ProgramID<N>,
+    pub function: Identifier<N>,
+    pub height: u32,
+    pub transaction_id: N::TransactionID,
+    pub transition_id: N::TransitionID,
+    /// The inputs, `None` for private and record inputs.
+    pub inputs: Vec<Option<Value<N>>>,
+    /// The outputs, `None` for private and record outputs.
+    pub outputs: Vec<Option<Value<N>>>,
+    /// The arguments passed to the finalize block.
+    pub finalize: Vec<Value<N>>,
+}
+
+impl<N: Network> ProgramCall<N> {
+    fn from_transition(
+        height: u32,
+        transaction_id: N::TransactionID,
+        transition: &Transition<N>,
+    ) -> Self {
+        let inputs = Self::public_inputs(transition);
+        let outputs = transition
+            .outputs()
+            .iter()
+            .map(|output| match output {
+                Output::Constant(_, Some(plaintext)) | Output::Public(_, Some(plaintext)) => {
+                    Some(Value::Plaintext(plaintext.clone()))
+                }
+                Output::Future(_, Some(future)) => Some(Value::Future(future.clone())),
+                _ => None,
+            })
+            .collect();
+        let finalize = transition
+            .outputs()
+            .iter()
+            .filter_map(|output| match output {
+                Output::Future(_, Some(future)) => Some(future.arguments()),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|argument| match argument {
+                Argument::Plaintext(plaintext) => Some(Value::Plaintext(plaintext.clone())),
+                Argument::Future(future) => Some(Value::Future(future.clone())),
+                #[allow(unreachable_patterns)]
+                _ => None,
+            })
+            .collect();
+
+        Self {
+            program_id: *transition.program_id(),
+            function: *transition.function_name(),
+            height,
+            transaction_id,
+            transition_id: *transition.id(),
+            inputs,
+            outputs,
+            finalize,
+        }
+    }
+
+    /// The inputs of `transition`, `None` for private and record inputs.
+    pub fn public_inputs(transition: &Transition<N>) -> Vec<Option<Value<N>>> {
+        transition
+            .inputs()
+            .iter()
+            .map(|input| match input {
+                Input::Constant(_, Some(plaintext)) | Input::Public(_, Some(plaintext)) => {
+                    Some(Value::Plaintext(plaintext.clone()))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The public input at `index`.
+    pub fn input(&self, index: usize) -> Result<Value<N>> {
+        match self.inputs.get(index) {
+            Some(Some(value)) => Ok(value.clone()),
+            Some(None) => Err(Error::Other(format!(
+                "Input {index} of {}/{} is not public",
+                self.program_id, self.function
+            ))),
+            None => Err(Error::Other(format!(
+                "{}/{} has no input {index}",
+                self.program_id, self.function
+            ))),
+        }
+    }
+
+    /// Decode the call into a typed call, such as the generated `<Program>Call` enums.
+    pub fn decode<C: DecodeCall<N>>(&self) -> Result<C> {
+        C::decode(self)
+    }
+}
+
+/// A typed call decoded from a `ProgramCall`.
+pub trait DecodeCall<N: Network>: Sized {
+    fn decode(call: &ProgramCall<N>) -> Result<Self>;
+}
+
+impl<N: Network> DecodeCall<N> for ProgramCall<N> {
+    fn decode(call: &ProgramCall<N>) -> Result<Self> {
+        Ok(call.clone())
+    }
+}
+
+/// Collects the calls to one program from the followed blocks.
+///
+/// Calls are delivered at least once: the checkpoint of a batch is saved
+/// when the next batch is polled, or on `commit`.
+pub struct Indexer<N: Network, S: CheckpointStore<N>> {
+    follower: BlockFollower<N>,
+    program_id: ProgramID<N>,
+    store: S,
+    pending: Option<Checkpoint<N>>,
+}
+
+impl<N: Network, S: CheckpointStore<N>> Indexer<N, S> {
+    /// Index `program_id` from the checkpoint in `store`, or from `start_height` without one.
+    pub fn new(
+        client: Client,
+        program_id: ProgramID<N>,
+        store: S,
+        start_height: u32,
+    ) -> Result<Self> {
+        let follower = match store.load()? {
+            Some(checkpoint) => {
+                log::info!(
+                    "Resuming {program_id} index after block {}",
+                    checkpoint.height
+                );
+                BlockFollower::resume(client, &checkpoint)
+            }
+            None => BlockFollower::new(client, start_height),
+        };
+        Ok(Self {
+            follower,
+            program_id,
+            store,
+            pending: None,
+        })
+    }
+
+    pub fn confirmations(mut self, confirmations: u32) -> Self {
+        self.follower = self.follower.confirmations(confirmations);
+        self
+    }
+
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.follower = self.follower.poll_interval(interval);
+        self
+    }
+
+    pub fn max_blocks_per_poll(mut self, max: u32) -> Self {
+        self.follower = self.follower.max_blocks_per_poll(max);
+        self
+    }
+
+    /// The calls to the program in every confirmed block not indexed yet.
+    pub async fn poll(&mut self) -> Result<Vec<ProgramCall<N>>> {
+        self.commit()?;
+        let blocks = self.follower.poll().await?;
+        self.pending = self.follower.checkpoint().filter(|_| !blocks.is_empty());
+        Ok(blocks.iter().flat_map(|block| self.calls(block)).collect())
+    }
+
+    /// Wait for the next confirmed block and return its calls to the program.
+    pub async fn next_block(&mut self) -> Result<Vec<ProgramCall<N>>> {
+        self.commit()?;
+        let block = self.follower.next_block().await?;
+        self.pending = self.follower.checkpoint();
+        Ok(self.calls(&block))
+    }
+
+    /// Save the checkpoint of the calls returned so far.
+    pub fn commit(&mut self) -> Result<()> {
+        if let Some(checkpoint) = self.pending.take() {
+            self.store.save(&checkpoint)?;
+        }
+        Ok(())
+    }
+
+    fn calls(&self, block: &Block<N>) -> Vec<ProgramCall<N>> {
+        block
+            .transactions()
+            .iter()
+            .filter(|confirmed| confirmed.is_accepted())
+            .flat_map(|confirmed| {
+                let transaction_id = confirmed.transaction().id();
+                confirmed
+                    .transaction()
+                    .transitions()
+                    .filter(|transition| *transition.program_id() == self.program_id)
+                    .map(move |transition| {
+                        ProgramCall::from_transition(block.height(), transaction_id, transition)
+                    })
+            })
+            .collect()
+    }
+}
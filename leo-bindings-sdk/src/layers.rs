@@ -0,0 +1,751 @@
+use crate::account::Account;
+use crate::bundle::AuthorizationBundle;
+use crate::error::{Error, Result};
+use crate::output::CallOutput;
+use crate::simulation::Simulation;
+use crate::stats::{DeploymentStats, ExecutionStats};
+use crate::vm_manager::VMManager;
+use snarkvm::prelude::{Identifier, Literal, Network, Plaintext, Program, ProgramID, Value};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Wraps a `VMManager` in another one that adds some behaviour.
+///
+/// Apply a layer with `VMManager::layer`, and combine layers with `Stack`.
+pub trait Layer<N: Network, M: VMManager<N>> {
+    type Manager: VMManager<N>;
+
+    fn layer(&self, inner: M) -> Self::Manager;
+}
+
+/// Applies `inner`, then `outer` around it.
+#[derive(Clone, Debug)]
+pub struct Stack<Inner, Outer> {
+    inner: Inner,
+    outer: Outer,
+}
+
+impl<Inner, Outer> Stack<Inner, Outer> {
+    pub fn new(inner: Inner, outer: Outer) -> Self {
+        Self { inner, outer }
+    }
+}
+
+impl<N, M, Inner, Outer> Layer<N, M> for Stack<Inner, Outer>
+where
+    N: Network,
+    M: VMManager<N>,
+    Inner: Layer<N, M>,
+    Outer: Layer<N, Inner::Manager>,
+{
+    type Manager = Outer::Manager;
+
+    fn layer(&self, inner: M) -> Self::Manager {
+        self.outer.layer(self.inner.layer(inner))
+    }
+}
+
+/// Logs every call with its duration.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TraceLayer;
+
+impl<N: Network, M: VMManager<N>> Layer<N, M> for TraceLayer {
+    type Manager = Traced<M>;
+
+    fn layer(&self, inner: M) -> Traced<M> {
+        Traced { inner }
+    }
+}
+
+/// A `VMManager` that logs every call, see `TraceLayer`.
+#[derive(Clone, Debug)]
+pub struct Traced<M> {
+    inner: M,
+}
+
+impl<M> Traced<M> {
+    /// Log `call` around `f`, reads at debug level and transactions at info level.
+    fn trace<T>(&self, level: log::Level, call: &str, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        let start = Instant::now();
+        log::log!(level, "{call} started");
+        let result = f();
+        match &result {
+            Ok(_) => log::log!(level, "{call} succeeded in {:.2?}", start.elapsed()),
+            Err(e) => log::warn!("{call} failed after {:.2?}: {e}", start.elapsed()),
+        }
+        result
+    }
+}
+
+impl<N: Network, M: VMManager<N>> VMManager<N> for Traced<M> {
+    fn program_exists(&self, program_id: &ProgramID<N>) -> Result<bool> {
+        self.trace(
+            log::Level::Debug,
+            &format!("program_exists {program_id}"),
+            || self.inner.program_exists(program_id),
+        )
+    }
+
+    fn program(&self, program_id: &ProgramID<N>) -> Result<Option<Program<N>>> {
+        self.trace(log::Level::Debug, &format!("program {program_id}"), || {
+            self.inner.program(program_id)
+        })
+    }
+
+    fn mapping_value(
+        &self,
+        program_id: &ProgramID<N>,
+        mapping_name: &Identifier<N>,
+        key: &Value<N>,
+    ) -> Result<Option<Value<N>>> {
+        let call = format!("mapping {program_id}/{mapping_name}[{key}]");
+        self.trace(log::Level::Debug, &call, || {
+            self.inner.mapping_value(program_id, mapping_name, key)
+        })
+    }
+
+    fn evaluate_view(
+        &self,
+        program_id: &ProgramID<N>,
+        view_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+    ) -> Result<Vec<Value<N>>> {
+        let call = format!("view {program_id}/{view_name}");
+        self.trace(log::Level::Debug, &call, || {
+            self.inner.evaluate_view(program_id, view_name, inputs)
+        })
+    }
+
+    fn deploy_and_broadcast(
+        &self,
+        deployer: &Account<N>,
+        program: &Program<N>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<()> {
+        let call = format!("deploy {} by {}", program.id(), deployer.address());
+        self.trace(log::Level::Info, &call, || {
+            self.inner
+                .deploy_and_broadcast(deployer, program, dependencies)
+        })
+    }
+
+    fn execute_and_broadcast(
+        &self,
+        account: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<Vec<CallOutput<N>>> {
+        let call = format!("{program_id}/{function_name} by {}", account.address());
+        self.trace(log::Level::Info, &call, || {
+            self.inner.execute_and_broadcast(
+                account,
+                program_id,
+                function_name,
+                inputs,
+                dependencies,
+            )
+        })
+    }
+
+    fn execute_sponsored(
+        &self,
+        account: &Account<N>,
+        fee_payer: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<Vec<CallOutput<N>>> {
+        let call = format!(
+            "{program_id}/{function_name} by {} paid by {}",
+            account.address(),
+            fee_payer.address()
+        );
+        self.trace(log::Level::Info, &call, || {
+            self.inner.execute_sponsored(
+                account,
+                fee_payer,
+                program_id,
+                function_name,
+                inputs,
+                dependencies,
+            )
+        })
+    }
+
+    fn simulate(
+        &self,
+        account: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<Simulation<N>> {
+        let call = format!(
+            "simulate {program_id}/{function_name} by {}",
+            account.address()
+        );
+        self.trace(log::Level::Debug, &call, || {
+            self.inner
+                .simulate(account, program_id, function_name, inputs, dependencies)
+        })
+    }
+
+    fn estimate_execution_fee(
+        &self,
+        account: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<ExecutionStats> {
+        let call = format!("estimate {program_id}/{function_name}");
+        self.trace(log::Level::Debug, &call, || {
+            self.inner.estimate_execution_fee(
+                account,
+                program_id,
+                function_name,
+                inputs,
+                dependencies,
+            )
+        })
+    }
+
+    fn estimate_deployment_fee(
+        &self,
+        deployer: &Account<N>,
+        program: &Program<N>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<DeploymentStats> {
+        let call = format!("estimate deploy {}", program.id());
+        self.trace(log::Level::Debug, &call, || {
+            self.inner
+                .estimate_deployment_fee(deployer, program, dependencies)
+        })
+    }
+
+    fn authorize_call(
+        &self,
+        account: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<AuthorizationBundle<N>> {
+        let call = format!("authorize {program_id}/{function_name}");
+        self.trace(log::Level::Debug, &call, || {
+            self.inner
+                .authorize_call(account, program_id, function_name, inputs, dependencies)
+        })
+    }
+
+    fn broadcast_authorization(
+        &self,
+        bundle: &AuthorizationBundle<N>,
+    ) -> Result<Vec<CallOutput<N>>> {
+        let call = format!(
+            "{}/{} authorized",
+            bundle.program_id(),
+            bundle.function_name()
+        );
+        self.trace(log::Level::Info, &call, || {
+            self.inner.broadcast_authorization(bundle)
+        })
+    }
+}
+
+/// Retries calls that fail with a transient error, see `Error::is_transient`.
+///
+/// Once `NetworkVm` may have submitted a transaction, its errors are no longer
+/// transient: failed broadcasts, failed requests to a prover that broadcasts
+/// itself, and failures while waiting for confirmation are returned as they are,
+/// so retries do not submit a call twice. A rate limited request was refused,
+/// so it is still retried.
+#[derive(Clone, Copy, Debug)]
+pub struct RetryLayer {
+    max_retries: u32,
+    backoff: Duration,
+}
+
+impl RetryLayer {
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            backoff: Duration::from_secs(1),
+        }
+    }
+
+    /// The delay before the first retry, doubled on each further retry.
+    ///
+    /// A `Retry-After` delay from the node takes precedence.
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+}
+
+impl<N: Network, M: VMManager<N>> Layer<N, M> for RetryLayer {
+    type Manager = Retrying<M>;
+
+    fn layer(&self, inner: M) -> Retrying<M> {
+        Retrying {
+            inner,
+            config: *self,
+        }
+    }
+}
+
+/// A `VMManager` that retries transient errors, see `RetryLayer`.
+#[derive(Clone, Debug)]
+pub struct Retrying<M> {
+    inner: M,
+    config: RetryLayer,
+}
+
+impl<M> Retrying<M> {
+    fn retry<T>(&self, mut f: impl FnMut() -> Result<T>) -> Result<T> {
+        let mut backoff = self.config.backoff;
+        let mut retries = 0;
+        loop {
+            match f() {
+                Err(e) if e.is_transient() && retries < self.config.max_retries => {
+                    let delay = match e {
+                        Error::RateLimited(Some(delay)) => delay,
+                        _ => backoff,
+                    };
+                    log::warn!("{e}, retrying in {delay:?}");
+                    std::thread::sleep(delay);
+                    backoff *= 2;
+                    retries += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl<N: Network, M: VMManager<N>> VMManager<N> for Retrying<M> {
+    fn program_exists(&self, program_id: &ProgramID<N>) -> Result<bool> {
+        self.retry(|| self.inner.program_exists(program_id))
+    }
+
+    fn program(&self, program_id: &ProgramID<N>) -> Result<Option<Program<N>>> {
+        self.retry(|| self.inner.program(program_id))
+    }
+
+    fn mapping_value(
+        &self,
+        program_id: &ProgramID<N>,
+        mapping_name: &Identifier<N>,
+        key: &Value<N>,
+    ) -> Result<Option<Value<N>>> {
+        self.retry(|| self.inner.mapping_value(program_id, mapping_name, key))
+    }
+
+    fn evaluate_view(
+        &self,
+        program_id: &ProgramID<N>,
+        view_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+    ) -> Result<Vec<Value<N>>> {
+        self.retry(|| {
+            self.inner
+                .evaluate_view(program_id, view_name, inputs.clone())
+        })
+    }
+
+    fn deploy_and_broadcast(
+        &self,
+        deployer: &Account<N>,
+        program: &Program<N>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<()> {
+        self.retry(|| {
+            self.inner
+                .deploy_and_broadcast(deployer, program, dependencies)
+        })
+    }
+
+    fn execute_and_broadcast(
+        &self,
+        account: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<Vec<CallOutput<N>>> {
+        self.retry(|| {
+            self.inner.execute_and_broadcast(
+                account,
+                program_id,
+                function_name,
+                inputs.clone(),
+                dependencies,
+            )
+        })
+    }
+
+    fn execute_sponsored(
+        &self,
+        account: &Account<N>,
+        fee_payer: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<Vec<CallOutput<N>>> {
+        self.retry(|| {
+            self.inner.execute_sponsored(
+                account,
+                fee_payer,
+                program_id,
+                function_name,
+                inputs.clone(),
+                dependencies,
+            )
+        })
+    }
+
+    fn simulate(
+        &self,
+        account: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<Simulation<N>> {
+        self.retry(|| {
+            self.inner.simulate(
+                account,
+                program_id,
+                function_name,
+                inputs.clone(),
+                dependencies,
+            )
+        })
+    }
+
+    fn estimate_execution_fee(
+        &self,
+        account: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<ExecutionStats> {
+        self.retry(|| {
+            self.inner.estimate_execution_fee(
+                account,
+                program_id,
+                function_name,
+                inputs.clone(),
+                dependencies,
+            )
+        })
+    }
+
+    fn estimate_deployment_fee(
+        &self,
+        deployer: &Account<N>,
+        program: &Program<N>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<DeploymentStats> {
+        self.retry(|| {
+            self.inner
+                .estimate_deployment_fee(deployer, program, dependencies)
+        })
+    }
+
+    fn authorize_call(
+        &self,
+        account: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<AuthorizationBundle<N>> {
+        self.retry(|| {
+            self.inner.authorize_call(
+                account,
+                program_id,
+                function_name,
+                inputs.clone(),
+                dependencies,
+            )
+        })
+    }
+
+    fn broadcast_authorization(
+        &self,
+        bundle: &AuthorizationBundle<N>,
+    ) -> Result<Vec<CallOutput<N>>> {
+        self.retry(|| self.inner.broadcast_authorization(bundle))
+    }
+}
+
+/// Rejects deployments and executions whose fee would take the fees paid past a budget.
+///
+/// The fee is estimated before the call and reserved from the budget, so a call
+/// never overshoots it and concurrent calls cannot both spend the last of it. The
+/// charge is the fee amount of the transaction, paid publicly or with a record, and
+/// is given back if the call fails. Managers layered by the same `BudgetLayer` share
+/// its budget.
+#[derive(Clone, Debug)]
+pub struct BudgetLayer {
+    budget: u64,
+    spent: Arc<Mutex<u64>>,
+}
+
+impl BudgetLayer {
+    /// A budget of `microcredits`.
+    pub fn new(microcredits: u64) -> Self {
+        Self {
+            budget: microcredits,
+            spent: Arc::new(Mutex::new(0)),
+        }
+    }
+}
+
+impl<N: Network, M: VMManager<N>> Layer<N, M> for BudgetLayer {
+    type Manager = Budgeted<M>;
+
+    fn layer(&self, inner: M) -> Budgeted<M> {
+        Budgeted {
+            inner,
+            budget: self.clone(),
+        }
+    }
+}
+
+/// A `VMManager` with a fee budget, see `BudgetLayer`.
+#[derive(Clone, Debug)]
+pub struct Budgeted<M> {
+    inner: M,
+    budget: BudgetLayer,
+}
+
+impl<M> Budgeted<M> {
+    /// The microcredits spent so far.
+    pub fn spent(&self) -> u64 {
+        *self.budget.spent.lock().unwrap()
+    }
+
+    /// The microcredits left in the budget.
+    pub fn remaining(&self) -> u64 {
+        self.budget.budget.saturating_sub(self.spent())
+    }
+
+    /// Reserve the fee returned by `fee` from the budget and run `f`, giving the fee back on failure.
+    fn charge<T>(
+        &self,
+        fee: impl FnOnce() -> Result<u64>,
+        f: impl FnOnce() -> Result<T>,
+    ) -> Result<T> {
+        let fee = fee()?;
+        {
+            let mut spent = self.budget.spent.lock().unwrap();
+            if spent.saturating_add(fee) > self.budget.budget {
+                return Err(Error::BudgetExceeded {
+                    budget: self.budget.budget,
+                    spent: *spent,
+                    fee,
+                });
+            }
+            *spent += fee;
+        }
+        let result = f();
+        if result.is_err() {
+            *self.budget.spent.lock().unwrap() -= fee;
+        }
+        result
+    }
+}
+
+/// The base and priority fee of a signed fee, which precede the execution ID in its inputs.
+fn authorized_fee<N: Network>(bundle: &AuthorizationBundle<N>) -> Result<u64> {
+    let request = bundle
+        .fee_authorization()
+        .peek_next()
+        .map_err(|e| Error::Other(format!("Empty fee authorization: {e}")))?;
+    let [.., base_fee, priority_fee, _] = request.inputs() else {
+        return Err(Error::Other(
+            "Fee authorization without amounts".to_string(),
+        ));
+    };
+    let amount = |value: &Value<N>| match value {
+        Value::Plaintext(Plaintext::Literal(Literal::U64(amount), _)) => Ok(**amount),
+        _ => Err(Error::Other(format!("Unexpected fee input {value}"))),
+    };
+    Ok(amount(base_fee)? + amount(priority_fee)?)
+}
+
+impl<N: Network, M: VMManager<N>> VMManager<N> for Budgeted<M> {
+    fn program_exists(&self, program_id: &ProgramID<N>) -> Result<bool> {
+        self.inner.program_exists(program_id)
+    }
+
+    fn program(&self, program_id: &ProgramID<N>) -> Result<Option<Program<N>>> {
+        self.inner.program(program_id)
+    }
+
+    fn mapping_value(
+        &self,
+        program_id: &ProgramID<N>,
+        mapping_name: &Identifier<N>,
+        key: &Value<N>,
+    ) -> Result<Option<Value<N>>> {
+        self.inner.mapping_value(program_id, mapping_name, key)
+    }
+
+    fn evaluate_view(
+        &self,
+        program_id: &ProgramID<N>,
+        view_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+    ) -> Result<Vec<Value<N>>> {
+        self.inner.evaluate_view(program_id, view_name, inputs)
+    }
+
+    fn deploy_and_broadcast(
+        &self,
+        deployer: &Account<N>,
+        program: &Program<N>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<()> {
+        self.charge(
+            || {
+                self.inner
+                    .estimate_deployment_fee(deployer, program, dependencies)
+                    .map(|stats| stats.total_fee)
+            },
+            || {
+                self.inner
+                    .deploy_and_broadcast(deployer, program, dependencies)
+            },
+        )
+    }
+
+    fn execute_and_broadcast(
+        &self,
+        account: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<Vec<CallOutput<N>>> {
+        self.charge(
+            || {
+                self.inner
+                    .estimate_execution_fee(
+                        account,
+                        program_id,
+                        function_name,
+                        inputs.clone(),
+                        dependencies,
+                    )
+                    .map(|stats| stats.total_fee)
+            },
+            || {
+                self.inner.execute_and_broadcast(
+                    account,
+                    program_id,
+                    function_name,
+                    inputs,
+                    dependencies,
+                )
+            },
+        )
+    }
+
+    fn execute_sponsored(
+        &self,
+        account: &Account<N>,
+        fee_payer: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<Vec<CallOutput<N>>> {
+        self.charge(
+            || {
+                self.inner
+                    .estimate_execution_fee(
+                        account,
+                        program_id,
+                        function_name,
+                        inputs.clone(),
+                        dependencies,
+                    )
+                    .map(|stats| stats.total_fee)
+            },
+            || {
+                self.inner.execute_sponsored(
+                    account,
+                    fee_payer,
+                    program_id,
+                    function_name,
+                    inputs,
+                    dependencies,
+                )
+            },
+        )
+    }
+
+    fn simulate(
+        &self,
+        account: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<Simulation<N>> {
+        self.inner
+            .simulate(account, program_id, function_name, inputs, dependencies)
+    }
+
+    fn estimate_execution_fee(
+        &self,
+        account: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<ExecutionStats> {
+        self.inner
+            .estimate_execution_fee(account, program_id, function_name, inputs, dependencies)
+    }
+
+    fn estimate_deployment_fee(
+        &self,
+        deployer: &Account<N>,
+        program: &Program<N>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<DeploymentStats> {
+        self.inner
+            .estimate_deployment_fee(deployer, program, dependencies)
+    }
+
+    fn authorize_call(
+        &self,
+        account: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<AuthorizationBundle<N>> {
+        self.inner
+            .authorize_call(account, program_id, function_name, inputs, dependencies)
+    }
+
+    fn broadcast_authorization(
+        &self,
+        bundle: &AuthorizationBundle<N>,
+    ) -> Result<Vec<CallOutput<N>>> {
+        self.charge(
+            || authorized_fee(bundle),
+            || self.inner.broadcast_authorization(bundle),
+        )
+    }
+}
@@ -1,34 +1,119 @@
 mod account;
 pub mod address;
+mod bundle;
 mod config;
 mod endpoints;
 mod error;
+mod failover;
+mod fees;
+mod finalize;
+mod fork;
+mod fuzz;
+mod gas;
+mod indexer;
+mod layers;
 pub mod local_chain;
+mod node;
+mod output;
+mod pending;
+mod prover;
+mod retry;
+mod server;
+mod simulation;
 mod stats;
+mod transcript;
 mod utils;
 mod value;
 mod vm_manager;
 
 pub use account::Account;
 pub use address::Address;
+pub use bundle::AuthorizationBundle;
 pub use value::{FromValue, ToValue};
-pub use config::{Client, Credentials};
+pub use config::{Client, ClientBuilder, Credentials};
 pub use endpoints::transactions::TransactionStatus;
 pub use error::{Error, Result};
+pub use failover::{EndpointHealth, Served};
+pub use indexer::{
+    BlockFollower, Checkpoint, CheckpointStore, DecodeCall, FileCheckpointStore, Indexer,
+    MemoryCheckpointStore, ProgramCall,
+};
+pub use fuzz::{Actors, Arbitrary, FUZZ_SEED_ENV, FuzzCall, FuzzFailure, FuzzStep, Fuzzer};
+pub use gas::{
+    GAS_SNAPSHOT_CHECK_ENV, GAS_SNAPSHOT_ENV, GasSnapshot, GasSnapshotGuard, flush_gas_snapshot,
+};
+pub use fees::{CreditsRecords, RecordSource, microcredits, select_fee_record};
+pub use layers::{BudgetLayer, Budgeted, Layer, RetryLayer, Retrying, Stack, TraceLayer, Traced};
 pub use local_chain::build_local_chain_bytes;
-pub use stats::{print_deployment_stats, print_execution_stats};
+pub use node::LocalNode;
+pub use output::{CallOutput, Encrypted, ProgramRecord, RecordOutput};
+pub use pending::PendingTransaction;
+pub use prover::{LocalProver, ProverServer};
+pub use simulation::{MappingDiff, MappingWrite, Receipt, Simulation};
+pub use stats::{
+    COST_REPORT_ENV, COST_REPORT_RUN_ENV, CostReport, DeploymentStats, ExecutionStats,
+    FunctionCosts, cost_report, print_deployment_stats, print_execution_stats,
+};
+pub use transcript::{
+    Divergence, RecordLayer, Recording, ReplayReport, Replayer, TranscriptEntry, TranscriptInput,
+    read_transcript,
+};
 pub use vm_manager::{LocalVM, LocalVMSnapshot, SnapshotStore};
 pub use vm_manager::{CONSENSUS_VERSION, NetworkVm, VMManager};
 
 pub use anyhow;
 pub use indexmap;
 pub use log;
+pub use proptest;
 pub use snarkvm;
 
-pub fn block_on<F: std::future::Future>(f: F) -> F::Output {
-    tokio::runtime::Builder::new_current_thread()
+static RUNTIME: std::sync::LazyLock<tokio::runtime::Runtime> = std::sync::LazyLock::new(|| {
+    tokio::runtime::Builder::new_multi_thread()
+        .thread_name("leo-bindings")
         .enable_all()
         .build()
         .expect("Failed to build tokio runtime")
-        .block_on(f)
+});
+
+/// The runtime `block_on` drives requests on, shared so `Client` connections are reused.
+pub fn runtime() -> &'static tokio::runtime::Runtime {
+    &RUNTIME
+}
+
+/// Run `f` to completion on the shared runtime.
+///
+/// Inside a multi-threaded tokio runtime, the calling worker hands its other tasks
+/// off with `block_in_place` instead of panicking on a nested `block_on`. Inside a
+/// current-thread runtime, `f` is polled on the calling thread, which blocks that
+/// runtime until `f` completes. `f` does not need to be `Send` in either case.
+pub fn block_on<F: std::future::Future>(f: F) -> F::Output {
+    let Ok(handle) = tokio::runtime::Handle::try_current() else {
+        return RUNTIME.block_on(f);
+    };
+    match handle.runtime_flavor() {
+        tokio::runtime::RuntimeFlavor::CurrentThread => poll_on_current_thread(f),
+        _ => tokio::task::block_in_place(|| RUNTIME.block_on(f)),
+    }
+}
+
+struct ThreadWaker(std::thread::Thread);
+
+impl std::task::Wake for ThreadWaker {
+    fn wake(self: std::sync::Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Poll `f` on this thread, with the shared runtime driving its I/O and timers.
+fn poll_on_current_thread<F: std::future::Future>(f: F) -> F::Output {
+    let _guard = RUNTIME.enter();
+    let waker = std::task::Waker::from(std::sync::Arc::new(ThreadWaker(std::thread::current())));
+    let mut cx = std::task::Context::from_waker(&waker);
+    let mut f = std::pin::pin!(f);
+    loop {
+        if let std::task::Poll::Ready(output) = f.as_mut().poll(&mut cx) {
+            return output;
+        }
+        std::thread::park();
+    }
 }
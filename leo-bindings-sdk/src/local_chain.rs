@@ -222,6 +222,21 @@ pub(crate) fn commit_transaction<R: Rng + CryptoRng>(
     transaction: &Transaction<TestnetV0>,
     rng: &mut R,
 ) -> Result<()> {
+    let (block, _) = speculate_transaction(vm, beacon_key, transaction, rng)?;
+    vm.add_next_block(&block)
+        .map_err(|e| Error::Other(format!("add_next_block: {e}")))?;
+    Ok(())
+}
+
+/// Build the next block with `transaction`, returning it with the finalize operations of the transaction.
+///
+/// The transaction is finalized speculatively, so nothing changes until the block is added.
+pub(crate) fn speculate_transaction<R: Rng + CryptoRng>(
+    vm: &VM<TestnetV0, ConsensusMemory<TestnetV0>>,
+    beacon_key: &snarkvm::prelude::PrivateKey<TestnetV0>,
+    transaction: &Transaction<TestnetV0>,
+    rng: &mut R,
+) -> Result<(Block<TestnetV0>, Vec<FinalizeOperation<TestnetV0>>)> {
     let dt = TestnetV0::BLOCK_TIME as i64;
     let (ratifications, transactions, aborted, ratified_finalize) = vm
         .speculate_local_proofless(
@@ -239,6 +254,10 @@ pub(crate) fn commit_transaction<R: Rng + CryptoRng>(
             "local_chain: proofless transaction aborted (ids): {aborted:?}"
         )));
     }
+    let operations = transactions
+        .iter()
+        .flat_map(|confirmed| confirmed.finalize_operations().iter().cloned())
+        .collect();
     let block = construct_next_block(
         vm,
         dt,
@@ -249,12 +268,10 @@ pub(crate) fn commit_transaction<R: Rng + CryptoRng>(
         ratified_finalize,
         rng,
     )?;
-    vm.add_next_block(&block)
-        .map_err(|e| Error::Other(format!("add_next_block: {e}")))?;
-    Ok(())
+    Ok((block, operations))
 }
 
-fn construct_finalize_global_state<C: ConsensusStorage<TestnetV0>>(
+pub(crate) fn construct_finalize_global_state<C: ConsensusStorage<TestnetV0>>(
     vm: &VM<TestnetV0, C>,
     time_since_last_block: i64,
 ) -> FinalizeGlobalState {
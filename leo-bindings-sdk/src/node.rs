@@ -0,0 +1,303 @@
+use crate::account::Account;
+use crate::error::{Error, Result};
+use crate::server::{HttpServer, Request, Response};
+use crate::vm_manager::{LocalVM, VMManager};
+use snarkvm::ledger::block::{Block, ConfirmedTransaction, Transaction};
+use snarkvm::ledger::store::BlockStore;
+use snarkvm::ledger::store::helpers::memory::BlockMemory;
+use snarkvm::prelude::*;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+/// An in-process stand-in for a snarkOS node, serving the `/v2/{network}/...`
+/// endpoints used by `Client` from a `LocalVM`.
+///
+/// Broadcast transactions are committed in a new block right away,
+/// so `NetworkVm` can run with real proving without a devnet.
+pub struct LocalNode {
+    server: HttpServer,
+    vm: LocalVM,
+}
+
+struct NodeState {
+    vm: LocalVM,
+    /// Transactions broadcast to this node, keyed by their unconfirmed ID.
+    confirmed: Mutex<HashMap<String, ConfirmedTransaction<TestnetV0>>>,
+}
+
+impl LocalNode {
+    /// Serve `vm` on a local port until the node is dropped.
+    pub fn spawn(vm: LocalVM) -> Result<Self> {
+        let state = Arc::new(NodeState {
+            vm: vm.clone(),
+            confirmed: Mutex::new(HashMap::new()),
+        });
+        let server = HttpServer::spawn(move |request| state.handle(request))?;
+        log::info!("🛰️ Local node listening on {}", server.url());
+        Ok(Self { server, vm })
+    }
+
+    /// The endpoint to pass to `Client::new`.
+    pub fn endpoint(&self) -> String {
+        self.server.url()
+    }
+
+    pub fn vm(&self) -> &LocalVM {
+        &self.vm
+    }
+}
+
+impl std::fmt::Debug for LocalNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LocalNode")
+            .field("endpoint", &self.endpoint())
+            .finish_non_exhaustive()
+    }
+}
+
+impl NodeState {
+    fn handle(&self, request: Request) -> Response {
+        let segments = request.segments();
+        let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+        let route = match segments.as_slice() {
+            ["v2", network, route @ ..] if *network == TestnetV0::SHORT_NAME => route,
+            _ => return Response::error(404, "Not found"),
+        };
+
+        let response = match (request.method.as_str(), route) {
+            ("GET", ["block", "height", "latest"]) => Ok(Response::text(
+                &self.block_store().current_block_height().to_string(),
+            )),
+            ("GET", ["block", "latest"]) => {
+                self.block(&self.block_store().current_block_height().to_string())
+            }
+            ("GET", ["block", height_or_hash]) => self.block(height_or_hash),
+            ("GET", ["block", height, "transactions"]) => self.block_transactions(height),
+            ("GET", ["blocks"]) => match (request.query("start"), request.query("end")) {
+                (Some(start), Some(end)) => self.blocks(&start, &end),
+                _ => Err(Error::Other("Missing start or end".to_string())),
+            },
+            ("GET", ["program", program_id]) => self.program(program_id),
+            ("GET", ["program", program_id, "latest_edition"]) => self.edition(program_id),
+            ("GET", ["program", program_id, "mappings"]) => self.mapping_names(program_id),
+            ("GET", ["program", program_id, "mapping", mapping_name, key]) => self.mapping(
+                program_id,
+                mapping_name,
+                key,
+                request.query("metadata").as_deref() == Some("true"),
+            ),
+            ("POST", ["program", program_id, "view", view_name]) => {
+                self.view(program_id, view_name, &request.body)
+            }
+            ("POST", ["transaction", "broadcast"]) => self.broadcast(&request.body),
+            ("GET", ["transaction", "confirmed", tx_id]) => self.confirmed(tx_id),
+            ("GET", ["transaction", tx_id]) => self.transaction(tx_id),
+            ("GET", ["stateRoot", "latest"]) => {
+                Ok(Response::json(&self.block_store().current_state_root()))
+            }
+            ("GET", ["committee", "latest"]) => {
+                let committee = self
+                    .vm
+                    .vm()
+                    .finalize_store()
+                    .committee_store()
+                    .current_committee()?;
+                Ok(Response::json(&committee))
+            }
+            ("GET", ["statePath", commitment]) => self.state_path(commitment),
+            ("GET", ["statePaths"]) => match request.query("commitments") {
+                Some(commitments) => self.state_paths(&commitments),
+                None => Err(Error::Other("Missing commitments".to_string())),
+            },
+            _ => return Response::error(404, "Not found"),
+        };
+
+        response.unwrap_or_else(|e| match e {
+            Error::NotFound(message) => Response::error(404, &message),
+            e => Response::error(500, &e.to_string()),
+        })
+    }
+
+    fn block_store(&self) -> &BlockStore<TestnetV0, BlockMemory<TestnetV0>> {
+        self.vm.vm().block_store()
+    }
+
+    fn parse_height(height: &str) -> Result<u32> {
+        height
+            .parse()
+            .map_err(|_| Error::Other(format!("Invalid block height '{height}'")))
+    }
+
+    fn block_at(&self, height: u32) -> Result<Block<TestnetV0>> {
+        self.vm
+            .block_at_height(height)
+            .map_err(|_| Error::NotFound(format!("Block {height} not found")))
+    }
+
+    fn block(&self, height_or_hash: &str) -> Result<Response> {
+        if let Ok(height) = height_or_hash.parse::<u32>() {
+            return Ok(Response::json(&self.block_at(height)?));
+        }
+        let hash = <TestnetV0 as Network>::BlockHash::from_str(height_or_hash)
+            .map_err(|e| Error::Other(format!("Invalid block hash: {e}")))?;
+        match self.block_store().get_block(&hash)? {
+            Some(block) => Ok(Response::json(&block)),
+            None => Err(Error::NotFound(format!("Block {hash} not found"))),
+        }
+    }
+
+    fn block_transactions(&self, height: &str) -> Result<Response> {
+        let block = self.block_at(Self::parse_height(height)?)?;
+        Ok(Response::json(block.transactions()))
+    }
+
+    fn blocks(&self, start: &str, end: &str) -> Result<Response> {
+        let (start, end) = (Self::parse_height(start)?, Self::parse_height(end)?);
+        let end = end.min(self.block_store().current_block_height() + 1);
+        let blocks = (start..end)
+            .map(|height| self.block_at(height))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Response::json(&blocks))
+    }
+
+    fn program(&self, program_id: &str) -> Result<Response> {
+        let program_id = ProgramID::<TestnetV0>::from_str(program_id)?;
+        let stack = self
+            .vm
+            .vm()
+            .process()
+            .get_stack(program_id)
+            .map_err(|_| Error::NotFound(format!("Missing program {program_id}")))?;
+        Ok(Response::json(&stack.program().to_string()))
+    }
+
+    fn edition(&self, program_id: &str) -> Result<Response> {
+        let program_id = ProgramID::<TestnetV0>::from_str(program_id)?;
+        let stack = self
+            .vm
+            .vm()
+            .process()
+            .get_stack(program_id)
+            .map_err(|_| Error::NotFound(format!("Missing program {program_id}")))?;
+        let edition: u16 = *stack.program_edition();
+        Ok(Response::json(&edition))
+    }
+
+    fn mapping(
+        &self,
+        program_id: &str,
+        mapping_name: &str,
+        key: &str,
+        metadata: bool,
+    ) -> Result<Response> {
+        let program_id = ProgramID::<TestnetV0>::from_str(program_id)?;
+        let mapping_name = Identifier::<TestnetV0>::from_str(mapping_name)?;
+        let key = Value::<TestnetV0>::from_str(key)?;
+        let value = self.vm.mapping_value(&program_id, &mapping_name, &key)?;
+        if metadata {
+            let height = self.block_store().current_block_height();
+            return Ok(Response::json(
+                &serde_json::json!({ "data": value, "height": height }),
+            ));
+        }
+        Ok(Response::json(&value))
+    }
+
+    fn mapping_names(&self, program_id: &str) -> Result<Response> {
+        let program_id = ProgramID::<TestnetV0>::from_str(program_id)?;
+        let stack = self
+            .vm
+            .vm()
+            .process()
+            .get_stack(program_id)
+            .map_err(|_| Error::NotFound(format!("Missing program {program_id}")))?;
+        let names: Vec<&Identifier<TestnetV0>> = stack.program().mappings().keys().collect();
+        Ok(Response::json(&names))
+    }
+
+    fn view(&self, program_id: &str, view_name: &str, body: &[u8]) -> Result<Response> {
+        let program_id = ProgramID::<TestnetV0>::from_str(program_id)?;
+        let view_name = Identifier::<TestnetV0>::from_str(view_name)?;
+        let inputs: Vec<String> = serde_json::from_slice(body)?;
+        let inputs = inputs
+            .iter()
+            .map(|input| Value::<TestnetV0>::from_str(input))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let outputs: Vec<String> = self
+            .vm
+            .evaluate_view(&program_id, &view_name, inputs)?
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        Ok(Response::json(&outputs))
+    }
+
+    fn broadcast(&self, body: &[u8]) -> Result<Response> {
+        let transaction: Transaction<TestnetV0> = serde_json::from_slice(body)?;
+        let tx_id = transaction.id();
+
+        // Hold the lock while committing so concurrent broadcasts build blocks in order.
+        let mut confirmed = self.confirmed.lock().unwrap();
+        let beacon_account = Account::dev_account(0).map_err(|e| Error::Other(e.to_string()))?;
+        crate::local_chain::commit_transaction(
+            self.vm.vm(),
+            beacon_account.private_key(),
+            &transaction,
+            &mut rand::rng(),
+        )?;
+
+        let block = self
+            .vm
+            .block_at_height(self.block_store().current_block_height())?;
+        for transaction in block.transactions().iter() {
+            if transaction.to_unconfirmed_transaction_id().ok() == Some(tx_id) {
+                confirmed.insert(tx_id.to_string(), transaction.clone());
+            }
+        }
+        log::info!("🛰️ Local node committed tx {tx_id}");
+        Ok(Response::json(&tx_id))
+    }
+
+    fn confirmed(&self, tx_id: &str) -> Result<Response> {
+        if let Some(transaction) = self.confirmed.lock().unwrap().get(tx_id) {
+            return Ok(Response::json(transaction));
+        }
+        let tx_id = <TestnetV0 as Network>::TransactionID::from_str(tx_id)
+            .map_err(|e| Error::Other(format!("Invalid transaction ID: {e}")))?;
+        match self.block_store().get_confirmed_transaction(tx_id)? {
+            Some(transaction) => Ok(Response::json(&transaction)),
+            None => Err(Error::NotFound(format!("Transaction {tx_id} not found"))),
+        }
+    }
+
+    fn transaction(&self, tx_id: &str) -> Result<Response> {
+        let tx_id = <TestnetV0 as Network>::TransactionID::from_str(tx_id)
+            .map_err(|e| Error::Other(format!("Invalid transaction ID: {e}")))?;
+        match self.block_store().get_transaction(&tx_id)? {
+            Some(transaction) => Ok(Response::json(&transaction)),
+            None => Err(Error::NotFound(format!("Transaction {tx_id} not found"))),
+        }
+    }
+
+    fn state_path(&self, commitment: &str) -> Result<Response> {
+        let commitment = Field::<TestnetV0>::from_str(commitment)?;
+        let state_path = self
+            .block_store()
+            .get_state_path_for_commitment(&commitment)?;
+        Ok(Response::json(&state_path))
+    }
+
+    fn state_paths(&self, commitments: &str) -> Result<Response> {
+        let state_paths = commitments
+            .split(',')
+            .map(|commitment| {
+                let commitment = Field::<TestnetV0>::from_str(commitment)?;
+                Ok(self
+                    .block_store()
+                    .get_state_path_for_commitment(&commitment)?)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Response::json(&state_paths))
+    }
+}
@@ -0,0 +1,205 @@
+use crate::account::Account;
+use crate::address::Address;
+use crate::error::{Error, Result};
+use crate::value::FromValue;
+use serde::{Deserialize, Serialize};
+use snarkvm::ledger::block::{Output, Transaction};
+use snarkvm::prelude::{Ciphertext, Field, Literal, Network, Owner, Plaintext, Record, Value};
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+/// A function output as seen by the caller.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum CallOutput<N: Network> {
+    /// A value the caller can read.
+    Value(Value<N>),
+    /// A record owned by another account.
+    Encrypted(Record<N, Ciphertext<N>>),
+    /// A record of another program owned by another account.
+    ///
+    /// The transition only holds its hash, which cannot be matched to a ciphertext without the
+    /// owner's view key.
+    External(Field<N>),
+}
+
+impl<N: Network> CallOutput<N> {
+    /// Returns the value, failing if the output is a record owned by another account.
+    pub fn into_value(self) -> Result<Value<N>> {
+        match self {
+            Self::Value(value) => Ok(value),
+            Self::Encrypted(_) | Self::External(_) => Err(Error::Other(
+                "Output is a record owned by another account".to_string(),
+            )),
+        }
+    }
+}
+
+impl<N: Network> From<Value<N>> for CallOutput<N> {
+    fn from(value: Value<N>) -> Self {
+        Self::Value(value)
+    }
+}
+
+impl<N: Network> fmt::Display for CallOutput<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Value(value) => fmt::Display::fmt(value, f),
+            Self::Encrypted(record) => fmt::Display::fmt(record, f),
+            Self::External(hash) => write!(f, "external record {hash}"),
+        }
+    }
+}
+
+/// A record type generated from a Leo program.
+pub trait ProgramRecord {
+    type Network: Network;
+}
+
+/// A record encrypted for its owner.
+pub struct Encrypted<T: ProgramRecord> {
+    record: Record<T::Network, Ciphertext<T::Network>>,
+    _record: PhantomData<T>,
+}
+
+impl<T: ProgramRecord> Encrypted<T> {
+    pub fn new(record: Record<T::Network, Ciphertext<T::Network>>) -> Self {
+        Self {
+            record,
+            _record: PhantomData,
+        }
+    }
+
+    pub fn ciphertext(&self) -> &Record<T::Network, Ciphertext<T::Network>> {
+        &self.record
+    }
+
+    pub fn is_owner(&self, account: &Account<T::Network>) -> bool {
+        self.record.is_owner(account.view_key())
+    }
+
+    /// Decrypt the record with the owner's account.
+    pub fn decrypt(&self, account: &Account<T::Network>) -> Result<T>
+    where
+        T: FromValue<T::Network>,
+    {
+        let record = self
+            .record
+            .decrypt(account.view_key())
+            .map_err(|e| Error::Other(format!("Failed to decrypt record: {e}")))?;
+        Ok(T::from_value(Value::Record(record)))
+    }
+}
+
+impl<T: ProgramRecord> Clone for Encrypted<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.record.clone())
+    }
+}
+
+impl<T: ProgramRecord> fmt::Debug for Encrypted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Encrypted").field(&self.record).finish()
+    }
+}
+
+impl<T: ProgramRecord> fmt::Display for Encrypted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.record, f)
+    }
+}
+
+impl<T: ProgramRecord> FromStr for Encrypted<T> {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(Self::new(Record::from_str(s)?))
+    }
+}
+
+/// A record output, decrypted if the caller owns it.
+#[derive(Clone, Debug)]
+pub enum RecordOutput<T: ProgramRecord> {
+    Owned(T),
+    Encrypted(Encrypted<T>),
+    /// The hash of an external record owned by another account.
+    External(Field<T::Network>),
+}
+
+impl<T: ProgramRecord + FromValue<T::Network>> RecordOutput<T> {
+    pub fn from_output(output: CallOutput<T::Network>) -> Self {
+        match output {
+            CallOutput::Value(value) => Self::Owned(T::from_value(value)),
+            CallOutput::Encrypted(record) => Self::Encrypted(Encrypted::new(record)),
+            CallOutput::External(hash) => Self::External(hash),
+        }
+    }
+
+    /// Returns the record if the caller owns it.
+    pub fn owned(self) -> Result<T> {
+        match self {
+            Self::Owned(record) => Ok(record),
+            Self::Encrypted(_) | Self::External(_) => Err(Error::Other(
+                "Record is owned by another account".to_string(),
+            )),
+        }
+    }
+
+    /// Returns the record, decrypting it with `account` if the caller does not own it.
+    pub fn decrypt(self, account: &Account<T::Network>) -> Result<T> {
+        match self {
+            Self::Owned(record) => Ok(record),
+            Self::Encrypted(encrypted) => encrypted.decrypt(account),
+            Self::External(hash) => Err(Error::Other(format!(
+                "External record {hash} has no ciphertext in the transition"
+            ))),
+        }
+    }
+}
+
+pub(crate) fn record_owner<N: Network>(record: &Record<N, Plaintext<N>>) -> Result<Address<N>> {
+    match record.owner() {
+        Owner::Public(address) => Ok(Address::from(*address)),
+        Owner::Private(Plaintext::Literal(Literal::Address(address), _)) => {
+            Ok(Address::from(*address))
+        }
+        _ => Err(Error::Other("Record owner is not an address".to_string())),
+    }
+}
+
+/// Keep the records in `outputs` that are not owned by `caller` encrypted,
+/// using the ciphertexts from `transaction`.
+pub(crate) fn caller_outputs<N: Network>(
+    outputs: Vec<Value<N>>,
+    transaction: &Transaction<N>,
+    caller: &Address<N>,
+) -> Result<Vec<CallOutput<N>>> {
+    outputs
+        .into_iter()
+        .map(|value| {
+            let record = match &value {
+                Value::Record(record) if record_owner(record)? != *caller => record,
+                _ => return Ok(CallOutput::Value(value)),
+            };
+            transaction
+                .transitions()
+                .flat_map(|transition| transition.outputs().iter())
+                .find_map(|output| match output {
+                    Output::Record(_, _, Some(ciphertext), _)
+                        if ciphertext.nonce() == record.nonce() =>
+                    {
+                        Some(CallOutput::Encrypted(ciphertext.clone()))
+                    }
+                    _ => None,
+                })
+                .ok_or_else(|| {
+                    Error::Other(format!(
+                        "Record ciphertext with nonce {} not found in transaction {}",
+                        record.nonce(),
+                        transaction.id()
+                    ))
+                })
+        })
+        .collect()
+}
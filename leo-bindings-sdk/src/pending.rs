@@ -0,0 +1,128 @@
+use crate::config::Client;
+use crate::endpoints::transactions::TransactionStatus;
+use crate::error::{Error, Result};
+use crate::output::CallOutput;
+use snarkvm::prelude::{Network, Transaction};
+use std::future::{Future, IntoFuture};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+type OnAccepted = Arc<Mutex<Option<Box<dyn FnOnce() + Send>>>>;
+
+/// A broadcast transaction that has not been confirmed yet.
+///
+/// Await it, or call `wait`, to get the call outputs once the transaction is accepted.
+#[derive(Clone)]
+pub struct PendingTransaction<N: Network> {
+    client: Client,
+    transaction: Transaction<N>,
+    outputs: Vec<CallOutput<N>>,
+    /// Runs once, the first time any clone sees the transaction accepted.
+    on_accepted: OnAccepted,
+}
+
+impl<N: Network> PendingTransaction<N> {
+    pub(crate) fn new(
+        client: Client,
+        transaction: Transaction<N>,
+        outputs: Vec<CallOutput<N>>,
+    ) -> Self {
+        Self {
+            client,
+            transaction,
+            outputs,
+            on_accepted: OnAccepted::default(),
+        }
+    }
+
+    /// Run `f` once the transaction is seen accepted by `poll`, `wait` or awaiting it.
+    pub(crate) fn on_accepted(self, f: impl FnOnce() + Send + 'static) -> Self {
+        *self.on_accepted.lock().unwrap() = Some(Box::new(f));
+        self
+    }
+
+    fn accepted(&self) {
+        if let Some(f) = self.on_accepted.lock().unwrap().take() {
+            f();
+        }
+    }
+
+    pub fn id(&self) -> N::TransactionID {
+        self.transaction.id()
+    }
+
+    pub fn transaction(&self) -> &Transaction<N> {
+        &self.transaction
+    }
+
+    /// The outputs the call returns once the transaction is accepted.
+    pub fn outputs(&self) -> &[CallOutput<N>] {
+        &self.outputs
+    }
+
+    /// Query the current status of the transaction once.
+    pub fn status(&self) -> Result<TransactionStatus> {
+        crate::block_on(self.client.transaction_status::<N>(&self.id()))
+    }
+
+    /// Check whether the transaction was accepted, failing if it was rejected.
+    pub fn poll(&self) -> Result<bool> {
+        match self.status()? {
+            TransactionStatus::Accepted => {
+                self.accepted();
+                Ok(true)
+            }
+            TransactionStatus::Pending => Ok(false),
+            TransactionStatus::Rejected(reason) => Err(Error::TransactionRejected {
+                tx_id: self.id().to_string(),
+                reason,
+            }),
+        }
+    }
+
+    /// Block until the transaction is accepted and return its outputs.
+    pub fn wait(self) -> Result<Vec<CallOutput<N>>> {
+        crate::block_on(self.into_future())
+    }
+
+    /// Wait for all `pending` transactions concurrently.
+    ///
+    /// The results are in the same order as `pending`.
+    pub fn wait_all(pending: Vec<Self>) -> Vec<Result<Vec<CallOutput<N>>>> {
+        let handles: Vec<_> = pending
+            .into_iter()
+            .map(|pending| crate::runtime().spawn(pending.into_future()))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| {
+                crate::block_on(handle)
+                    .unwrap_or_else(|e| Err(Error::Other(format!("Confirmation task failed: {e}"))))
+            })
+            .collect()
+    }
+}
+
+impl<N: Network> IntoFuture for PendingTransaction<N> {
+    type Output = Result<Vec<CallOutput<N>>>;
+    type IntoFuture = Pin<Box<dyn Future<Output = Self::Output> + Send>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(async move {
+            self.client
+                .wait_for_transaction::<N>(&self.transaction.id())
+                .await?;
+            log::info!("✅ Transaction confirmed: {}", self.transaction.id());
+            self.accepted();
+            Ok(self.outputs)
+        })
+    }
+}
+
+impl<N: Network> std::fmt::Debug for PendingTransaction<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PendingTransaction")
+            .field("id", &self.id())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,158 @@
+use crate::account::Account;
+use crate::config::Credentials;
+use crate::endpoints::proving::{ProvingRequest, ProvingResponse};
+use crate::error::{Error, Result};
+use crate::server::{HttpServer, Request, Response};
+use crate::vm_manager::{CONSENSUS_VERSION, NetworkVm};
+use snarkvm::prelude::*;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const LOCAL_JWT: &str = "local-prover";
+
+/// A stand-in for the delegated proving service that proves with `NetworkVm::vm`.
+///
+/// It serves the same JWT and proving endpoints as the Provable API,
+/// so the delegated proving path can be tested against a local devnet.
+pub struct LocalProver<N: Network> {
+    vm: NetworkVm<N>,
+    fee_payer: Option<Account<N>>,
+}
+
+impl<N: Network> LocalProver<N> {
+    pub fn new(vm: NetworkVm<N>) -> Self {
+        Self {
+            vm,
+            fee_payer: None,
+        }
+    }
+
+    /// Pay the fee of requests without a fee authorization from the public balance of `fee_payer`.
+    pub fn with_fee_payer(mut self, fee_payer: Account<N>) -> Self {
+        self.fee_payer = Some(fee_payer);
+        self
+    }
+
+    /// Serve the prover on a local port until the returned server is dropped.
+    pub fn spawn(self) -> Result<ProverServer> {
+        let prover = Arc::new(self);
+        let server = HttpServer::spawn(move |request| prover.handle(request))?;
+        Ok(ProverServer { server })
+    }
+
+    fn handle(&self, request: Request) -> Response {
+        let segments = request.segments();
+        let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+        match (request.method.as_str(), segments.as_slice()) {
+            ("POST", ["jwts", _]) => {
+                let expires_at = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs()
+                    + 3600;
+                Response::json(&serde_json::json!({ "exp": expires_at }))
+                    .with_header("Authorization", &format!("Bearer {LOCAL_JWT}"))
+            }
+            ("POST", ["prove", network, "prove"]) if *network == N::SHORT_NAME => {
+                let expected = format!("Bearer {LOCAL_JWT}");
+                if request.header("Authorization") != Some(expected.as_str()) {
+                    return Response::error(401, "Invalid JWT");
+                }
+                match self.prove(&request.body) {
+                    Ok(response) => Response::json(&response),
+                    Err(e) => Response::error(400, &e.to_string()),
+                }
+            }
+            _ => Response::error(404, "Not found"),
+        }
+    }
+
+    fn prove(&self, body: &[u8]) -> Result<ProvingResponse> {
+        let request: ProvingRequest = serde_json::from_slice(body)?;
+        let authorization: Authorization<N> = serde_json::from_value(request.authorization)?;
+        let fee_authorization: Option<Authorization<N>> = request
+            .fee_authorization
+            .map(serde_json::from_value)
+            .transpose()?;
+
+        let main_program = *authorization
+            .peek_next()
+            .map_err(|e| Error::Other(format!("Failed to peek authorization: {e}")))?
+            .program_id();
+        let dependencies: Vec<ProgramID<N>> = authorization
+            .transitions()
+            .values()
+            .map(|transition| *transition.program_id())
+            .filter(|program_id| *program_id != main_program)
+            .collect();
+        self.vm
+            .ensure_program_loaded(&main_program, &dependencies)?;
+
+        let vm = self.vm.vm();
+        let rng = &mut rand::rng();
+        let fee_authorization = match fee_authorization {
+            Some(fee_authorization) => fee_authorization,
+            None => {
+                let fee_payer = self
+                    .fee_payer
+                    .as_ref()
+                    .ok_or_else(|| Error::Other("Fee authorization required".to_string()))?;
+                let (base_fee, _) = execution_cost_for_authorization(
+                    &vm.process().lock(),
+                    &authorization,
+                    CONSENSUS_VERSION,
+                )
+                .map_err(|e| Error::Other(format!("Failed to calculate execution cost: {e}")))?;
+                let execution_id = authorization
+                    .to_execution_id()
+                    .map_err(|e| Error::Other(format!("Failed to compute execution ID: {e}")))?;
+                vm.authorize_fee_public(fee_payer.private_key(), base_fee, 0, execution_id, rng)
+                    .map_err(|e| Error::Other(format!("Failed to authorize fee: {e}")))?
+            }
+        };
+
+        let query = NetworkVm::<N>::create_query(self.vm.client().endpoint())?;
+        let transaction = vm
+            .execute_authorization(authorization, Some(fee_authorization), Some(&query), rng)
+            .map_err(|e| Error::Other(format!("Failed to prove authorization: {e}")))?;
+        log::info!("🔏 Local prover proved tx: {}", transaction.id());
+
+        if request.broadcast {
+            crate::block_on(self.vm.client().broadcast(&transaction))?;
+        }
+
+        Ok(ProvingResponse {
+            transaction: serde_json::to_value(&transaction)?,
+        })
+    }
+}
+
+/// A running `LocalProver`, stopped when dropped.
+pub struct ProverServer {
+    server: HttpServer,
+}
+
+impl ProverServer {
+    /// The URL to pass to `Client::with_prover_url`.
+    pub fn prover_url(&self) -> String {
+        format!("{}/prove", self.server.url())
+    }
+
+    /// The URL to pass to `Credentials::with_jwt_url`.
+    pub fn jwt_url(&self) -> String {
+        format!("{}/jwts", self.server.url())
+    }
+
+    /// Credentials accepted by the local prover.
+    pub fn credentials(&self) -> Credentials {
+        Credentials::new("local", "local").with_jwt_url(&self.jwt_url())
+    }
+}
+
+impl std::fmt::Debug for ProverServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProverServer")
+            .field("url", &self.server.url())
+            .finish()
+    }
+}
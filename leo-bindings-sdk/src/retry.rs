@@ -0,0 +1,70 @@
+use http::Extensions;
+use reqwest::{Request, Response, StatusCode};
+use reqwest_middleware::{Middleware, Next};
+use reqwest_retry::{DefaultRetryableStrategy, Retryable, RetryableStrategy};
+use std::time::Duration;
+
+/// The delay requested by a `Retry-After` header in seconds, on 429 and 503 responses.
+///
+/// HTTP-date values are not supported and are ignored.
+pub(crate) fn retry_after(response: &Response) -> Option<Duration> {
+    if response.status() != StatusCode::TOO_MANY_REQUESTS
+        && response.status() != StatusCode::SERVICE_UNAVAILABLE
+    {
+        return None;
+    }
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// Retries responses with a `Retry-After` header after the requested delay.
+pub(crate) struct RetryAfterMiddleware {
+    pub(crate) max_retries: u32,
+    /// Longer delays are returned to the caller as `Error::RateLimited`.
+    pub(crate) max_delay: Duration,
+}
+
+#[async_trait::async_trait]
+impl Middleware for RetryAfterMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let mut retries = 0;
+        loop {
+            let Some(attempt) = req.try_clone() else {
+                return next.run(req, extensions).await;
+            };
+            let response = next.clone().run(attempt, extensions).await?;
+            match retry_after(&response) {
+                Some(delay) if retries < self.max_retries && delay <= self.max_delay => {
+                    log::debug!("Rate limited on {}, retrying in {delay:?}", req.url());
+                    retries += 1;
+                    tokio::time::sleep(delay).await;
+                }
+                _ => return Ok(response),
+            }
+        }
+    }
+}
+
+/// The default transient retry strategy, leaving `Retry-After` responses to `RetryAfterMiddleware`.
+pub(crate) struct RetryStrategy;
+
+impl RetryableStrategy for RetryStrategy {
+    fn handle(&self, res: &reqwest_middleware::Result<Response>) -> Option<Retryable> {
+        match res {
+            Ok(response) if retry_after(response).is_some() => None,
+            _ => DefaultRetryableStrategy.handle(res),
+        }
+    }
+}
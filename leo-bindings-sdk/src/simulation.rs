@@ -0,0 +1,158 @@
+use crate::output::CallOutput;
+use indexmap::{IndexMap, IndexSet};
+use snarkvm::prelude::{Identifier, Network, Plaintext, ProgramID, Value};
+use std::fmt;
+use std::hash::Hash;
+
+/// The entries of some mappings, keyed by program and mapping name.
+pub(crate) type MappingEntries<N> =
+    IndexMap<(ProgramID<N>, Identifier<N>), IndexMap<Plaintext<N>, Value<N>>>;
+
+/// Some keys of some mappings, keyed by program and mapping name, with `None` for all keys.
+pub(crate) type MappingKeys<N> =
+    IndexMap<(ProgramID<N>, Identifier<N>), Option<IndexSet<Plaintext<N>>>>;
+
+/// A mapping entry written by a call.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MappingWrite<N: Network> {
+    pub program_id: ProgramID<N>,
+    pub mapping: Identifier<N>,
+    pub key: Plaintext<N>,
+    /// The value before the call, `None` if the key was inserted.
+    pub old: Option<Value<N>>,
+    /// The value after the call, `None` if the key was removed.
+    pub new: Option<Value<N>>,
+}
+
+impl<N: Network> fmt::Display for MappingWrite<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |value: &Option<Value<N>>| match value {
+            Some(value) => value.to_string(),
+            None => "none".to_string(),
+        };
+        write!(
+            f,
+            "{}/{}[{}]: {} -> {}",
+            self.program_id,
+            self.mapping,
+            self.key,
+            show(&self.old),
+            show(&self.new)
+        )
+    }
+}
+
+/// The result of a call that was executed and finalized, but not committed.
+#[derive(Clone, Debug)]
+pub struct Simulation<N: Network> {
+    pub outputs: Vec<CallOutput<N>>,
+    pub writes: Vec<MappingWrite<N>>,
+}
+
+impl<N: Network> Simulation<N> {
+    /// The writes to `mapping` of `program_id`.
+    pub fn writes_to<'a>(
+        &'a self,
+        program_id: &'a ProgramID<N>,
+        mapping: &'a str,
+    ) -> impl Iterator<Item = &'a MappingWrite<N>> {
+        self.writes.iter().filter(move |write| {
+            write.program_id == *program_id && write.mapping.to_string() == mapping
+        })
+    }
+}
+
+/// The outputs and mapping writes of a committed call.
+#[derive(Clone, Debug)]
+pub struct Receipt<N: Network> {
+    pub transaction_id: N::TransactionID,
+    pub outputs: Vec<CallOutput<N>>,
+    pub writes: Vec<MappingWrite<N>>,
+}
+
+/// The entries of one typed mapping that differ between two states.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MappingDiff<K, V> {
+    pub added: Vec<(K, V)>,
+    pub removed: Vec<(K, V)>,
+    /// The key with its value before and after.
+    pub changed: Vec<(K, V, V)>,
+}
+
+impl<K: Clone + Hash + Eq, V: Clone + PartialEq> MappingDiff<K, V> {
+    pub fn new(before: &IndexMap<K, V>, after: &IndexMap<K, V>) -> Self {
+        let mut diff = Self {
+            added: Vec::new(),
+            removed: Vec::new(),
+            changed: Vec::new(),
+        };
+        for (key, new) in after {
+            match before.get(key) {
+                None => diff.added.push((key.clone(), new.clone())),
+                Some(old) if old != new => {
+                    diff.changed.push((key.clone(), old.clone(), new.clone()))
+                }
+                Some(_) => {}
+            }
+        }
+        for (key, old) in before {
+            if !after.contains_key(key) {
+                diff.removed.push((key.clone(), old.clone()));
+            }
+        }
+        diff
+    }
+}
+
+impl<K, V> MappingDiff<K, V> {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Display for MappingDiff<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (key, value) in &self.added {
+            writeln!(f, "  + {key:?}: {value:?}")?;
+        }
+        for (key, value) in &self.removed {
+            writeln!(f, "  - {key:?}: {value:?}")?;
+        }
+        for (key, old, new) in &self.changed {
+            writeln!(f, "  ~ {key:?}: {old:?} -> {new:?}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The entries that differ between `before` and `after`.
+pub(crate) fn mapping_writes<N: Network>(
+    before: &MappingEntries<N>,
+    after: &MappingEntries<N>,
+) -> Vec<MappingWrite<N>> {
+    let empty = IndexMap::new();
+    let mut writes = Vec::new();
+    for ((program_id, mapping), new_entries) in after {
+        let old_entries = before.get(&(*program_id, *mapping)).unwrap_or(&empty);
+        let write =
+            |key: &Plaintext<N>, old: Option<&Value<N>>, new: Option<&Value<N>>| MappingWrite {
+                program_id: *program_id,
+                mapping: *mapping,
+                key: key.clone(),
+                old: old.cloned(),
+                new: new.cloned(),
+            };
+        for (key, new) in new_entries {
+            let old = old_entries.get(key);
+            if old != Some(new) {
+                writes.push(write(key, old, Some(new)));
+            }
+        }
+        for (key, old) in old_entries {
+            if !new_entries.contains_key(key) {
+                writes.push(write(key, Some(old), None));
+            }
+        }
+    }
+    writes
+}
@@ -1,5 +1,8 @@
+use crate::error::Error;
+use crate::gas::Backend;
 use colored::*;
 use num_format::{Locale, ToFormattedString};
+use serde::{Deserialize, Serialize};
 use snarkvm::{
     ledger::store::helpers::memory::ConsensusMemory,
     prelude::{
@@ -7,6 +10,353 @@ use snarkvm::{
         execution_cost,
     },
 };
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+
+/// Environment variable naming the file the cost report is written to after every call.
+pub const COST_REPORT_ENV: &str = "LEO_BINDINGS_COST_REPORT";
+
+/// Environment variable naming the run a cost report file belongs to.
+///
+/// A process of another run replaces the file instead of adding to it. By default the
+/// run is the parent process, so the test binaries of one `cargo test` share a report.
+pub const COST_REPORT_RUN_ENV: &str = "LEO_BINDINGS_COST_REPORT_RUN";
+
+/// Size and cost of a deployment, in microcredits.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeploymentStats {
+    pub program: String,
+    pub variables: u64,
+    pub constraints: u64,
+    pub storage_cost: u64,
+    pub synthesis_cost: u64,
+    pub namespace_cost: u64,
+    pub constructor_cost: u64,
+    pub priority_fee: u64,
+    pub total_fee: u64,
+}
+
+impl DeploymentStats {
+    pub fn new<N: Network>(
+        vm: &VM<N, ConsensusMemory<N>>,
+        program_id: &str,
+        deployment: &Deployment<N>,
+        priority_fee: Option<u64>,
+        consensus_version: ConsensusVersion,
+    ) -> Result<Self> {
+        let (base_fee, (storage_cost, synthesis_cost, constructor_cost, namespace_cost)) =
+            deployment_cost(&vm.process().lock(), deployment, consensus_version)?;
+        let priority_fee = priority_fee.unwrap_or(0);
+        Ok(Self {
+            program: program_id.to_string(),
+            variables: deployment.num_combined_variables()?,
+            constraints: deployment.num_combined_constraints()?,
+            storage_cost,
+            synthesis_cost,
+            namespace_cost,
+            constructor_cost,
+            priority_fee,
+            total_fee: base_fee + priority_fee,
+        })
+    }
+}
+
+/// Cost of a single function call, in microcredits.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutionStats {
+    pub program: String,
+    pub function: String,
+    pub storage_cost: u64,
+    pub execution_cost: u64,
+    pub priority_fee: u64,
+    pub total_fee: u64,
+}
+
+impl ExecutionStats {
+    pub fn new<N: Network>(
+        vm: &VM<N, ConsensusMemory<N>>,
+        execution: &Execution<N>,
+        priority_fee: Option<u64>,
+        consensus_version: ConsensusVersion,
+    ) -> Result<Self> {
+        let (base_fee, (storage_cost, execution_cost)) =
+            execution_cost(&vm.process().lock(), execution, consensus_version)?;
+        // The root transition is the last one in an execution.
+        let root = execution
+            .transitions()
+            .last()
+            .ok_or_else(|| snarkvm::prelude::Error::msg("Execution has no transitions"))?;
+        let priority_fee = priority_fee.unwrap_or(0);
+        Ok(Self {
+            program: root.program_id().to_string(),
+            function: root.function_name().to_string(),
+            storage_cost,
+            execution_cost,
+            priority_fee,
+            total_fee: base_fee + priority_fee,
+        })
+    }
+}
+
+/// Costs of one function aggregated over every call.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionCosts {
+    pub calls: u64,
+    pub min_fee: u64,
+    pub max_fee: u64,
+    pub total_fees: u64,
+    /// Breakdown of the most recent call.
+    pub last: ExecutionStats,
+}
+
+impl FunctionCosts {
+    pub fn mean_fee(&self) -> u64 {
+        self.total_fees.checked_div(self.calls).unwrap_or(0)
+    }
+}
+
+/// Deployment and execution costs aggregated per program and function.
+///
+/// Every `NetworkVm` and `LocalVM` call is recorded in a process-wide report, see [`cost_report`].
+/// Note that `LocalVM` transactions are proofless, so their storage cost is lower than on a network.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CostReport {
+    /// The run that wrote the report file, see [`COST_REPORT_RUN_ENV`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run: Option<String>,
+    /// Latest deployment per program.
+    pub deployments: BTreeMap<String, DeploymentStats>,
+    /// Calls keyed by `program/function`.
+    pub executions: BTreeMap<String, FunctionCosts>,
+}
+
+impl CostReport {
+    pub fn record_deployment(&mut self, stats: DeploymentStats) {
+        self.deployments.insert(stats.program.clone(), stats);
+    }
+
+    pub fn record_execution(&mut self, stats: ExecutionStats) {
+        let key = format!("{}/{}", stats.program, stats.function);
+        let costs = self.executions.entry(key).or_insert_with(|| FunctionCosts {
+            min_fee: u64::MAX,
+            ..Default::default()
+        });
+        costs.calls += 1;
+        costs.min_fee = costs.min_fee.min(stats.total_fee);
+        costs.max_fee = costs.max_fee.max(stats.total_fee);
+        costs.total_fees += stats.total_fee;
+        costs.last = stats;
+    }
+
+    /// Add the calls of `other` to this report.
+    pub fn merge(&mut self, other: &CostReport) {
+        for (program, stats) in &other.deployments {
+            self.deployments.insert(program.clone(), stats.clone());
+        }
+        for (key, other) in &other.executions {
+            let costs = self
+                .executions
+                .entry(key.clone())
+                .or_insert_with(|| FunctionCosts {
+                    min_fee: u64::MAX,
+                    ..Default::default()
+                });
+            costs.calls += other.calls;
+            costs.min_fee = costs.min_fee.min(other.min_fee);
+            costs.max_fee = costs.max_fee.max(other.max_fee);
+            costs.total_fees += other.total_fees;
+            costs.last = other.last.clone();
+        }
+    }
+
+    pub fn read(path: impl AsRef<Path>) -> crate::error::Result<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path).map_err(|e| {
+            Error::Other(format!(
+                "Failed to read cost report {}: {e}",
+                path.display()
+            ))
+        })?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("cost report is serializable")
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::from("# Cost report\n");
+        if !self.deployments.is_empty() {
+            md.push_str("\n## Deployments\n\n");
+            md.push_str("| Program | Variables | Constraints | Storage | Synthesis | Namespace | Constructor | Priority fee | Total fee |\n");
+            md.push_str("|---|---:|---:|---:|---:|---:|---:|---:|---:|\n");
+            for stats in self.deployments.values() {
+                let _ = writeln!(
+                    md,
+                    "| `{}` | {} | {} | {} | {} | {} | {} | {} | {} |",
+                    stats.program,
+                    stats.variables.to_formatted_string(&Locale::en),
+                    stats.constraints.to_formatted_string(&Locale::en),
+                    credits(stats.storage_cost),
+                    credits(stats.synthesis_cost),
+                    credits(stats.namespace_cost),
+                    credits(stats.constructor_cost),
+                    credits(stats.priority_fee),
+                    credits(stats.total_fee),
+                );
+            }
+        }
+        if !self.executions.is_empty() {
+            md.push_str("\n## Executions\n\n");
+            md.push_str(
+                "| Function | Calls | Storage | Execution | Min fee | Mean fee | Max fee |\n",
+            );
+            md.push_str("|---|---:|---:|---:|---:|---:|---:|\n");
+            for (key, costs) in &self.executions {
+                let _ = writeln!(
+                    md,
+                    "| `{key}` | {} | {} | {} | {} | {} | {} |",
+                    costs.calls,
+                    credits(costs.last.storage_cost),
+                    credits(costs.last.execution_cost),
+                    credits(costs.min_fee),
+                    credits(costs.mean_fee()),
+                    credits(costs.max_fee),
+                );
+            }
+        }
+        md
+    }
+
+    /// Write the report as JSON, or as Markdown if `path` ends in `.md`.
+    pub fn write(&self, path: impl AsRef<Path>) -> crate::error::Result<()> {
+        let path = path.as_ref();
+        let contents = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("md") => self.to_markdown(),
+            _ => self.to_json(),
+        };
+        std::fs::write(path, contents).map_err(|e| {
+            Error::Other(format!(
+                "Failed to write cost report {}: {e}",
+                path.display()
+            ))
+        })
+    }
+}
+
+fn credits(microcredits: u64) -> String {
+    format!("{:.6}", microcredits as f64 / 1_000_000.0)
+}
+
+struct GlobalReport {
+    report: CostReport,
+    /// Report file and the calls of earlier test binaries of this run it already held.
+    output: Option<(PathBuf, CostReport)>,
+}
+
+static REPORT: LazyLock<Mutex<GlobalReport>> = LazyLock::new(|| {
+    let output = std::env::var(COST_REPORT_ENV).ok().map(|path| {
+        let path = PathBuf::from(path);
+        let run = std::env::var(COST_REPORT_RUN_ENV).unwrap_or_else(|_| parent_run());
+        let previous = match CostReport::read(path.with_extension("json")) {
+            Ok(previous) if previous.run.as_ref() == Some(&run) => previous,
+            _ => CostReport {
+                run: Some(run),
+                ..Default::default()
+            },
+        };
+        (path, previous)
+    });
+    Mutex::new(GlobalReport {
+        report: CostReport::default(),
+        output,
+    })
+});
+
+#[cfg(unix)]
+fn parent_run() -> String {
+    format!("ppid-{}", std::os::unix::process::parent_id())
+}
+
+#[cfg(not(unix))]
+fn parent_run() -> String {
+    format!("pid-{}", std::process::id())
+}
+
+/// Costs recorded so far by this process.
+pub fn cost_report() -> CostReport {
+    REPORT.lock().unwrap().report.clone()
+}
+
+/// Fail if the cost of a deployment regressed against the gas snapshot.
+pub(crate) fn check_deployment(
+    backend: Backend,
+    stats: &DeploymentStats,
+) -> crate::error::Result<()> {
+    crate::gas::check(
+        backend,
+        &stats.program,
+        stats.total_fee - stats.priority_fee,
+    )
+}
+
+/// Fail if the cost of a call regressed against the gas snapshot.
+pub(crate) fn check_execution(
+    backend: Backend,
+    stats: &ExecutionStats,
+) -> crate::error::Result<()> {
+    crate::gas::check(
+        backend,
+        &format!("{}/{}", stats.program, stats.function),
+        stats.total_fee - stats.priority_fee,
+    )
+}
+
+/// Record a deployment in the cost report and the gas snapshot.
+pub(crate) fn record_deployment(backend: Backend, stats: DeploymentStats) {
+    crate::gas::record(
+        backend,
+        &stats.program,
+        stats.total_fee - stats.priority_fee,
+    );
+    update_report(|report| report.record_deployment(stats));
+}
+
+/// Record a call in the cost report and the gas snapshot.
+pub(crate) fn record_execution(backend: Backend, stats: ExecutionStats) {
+    crate::gas::record(
+        backend,
+        &format!("{}/{}", stats.program, stats.function),
+        stats.total_fee - stats.priority_fee,
+    );
+    update_report(|report| report.record_execution(stats));
+}
+
+/// Record into the process-wide report and, if `LEO_BINDINGS_COST_REPORT` is set, rewrite the report file.
+///
+/// The file accumulates the calls of every process of the same run, so each test binary adds to
+/// the same report, while the first process of a new run replaces it.
+/// A `.md` file gets a Markdown table next to a `.json` file holding the data.
+fn update_report(record: impl FnOnce(&mut CostReport)) {
+    let mut global = REPORT.lock().unwrap();
+    record(&mut global.report);
+    if let Some((path, previous)) = &global.output {
+        let mut report = previous.clone();
+        report.merge(&global.report);
+        let result = if path.extension().is_some_and(|ext| ext == "md") {
+            report
+                .write(path.with_extension("json"))
+                .and_then(|_| report.write(path))
+        } else {
+            report.write(path)
+        };
+        if let Err(e) = result {
+            log::warn!("{e}");
+        }
+    }
+}
 
 /// Pretty-print deployment statistics.
 pub fn print_deployment_stats<N: Network>(
@@ -15,15 +365,20 @@ pub fn print_deployment_stats<N: Network>(
     deployment: &Deployment<N>,
     priority_fee: Option<u64>,
     consensus_version: ConsensusVersion,
-) -> Result<()> {
-    let variables = deployment.num_combined_variables()?;
-    let constraints = deployment.num_combined_constraints()?;
-    let (base_fee, (storage_cost, synthesis_cost, constructor_cost, namespace_cost)) =
-        deployment_cost(&vm.process().lock(), deployment, consensus_version)?;
+) -> Result<DeploymentStats> {
+    let stats = DeploymentStats::new(vm, program_id, deployment, priority_fee, consensus_version)?;
+    let DeploymentStats {
+        variables,
+        constraints,
+        storage_cost,
+        synthesis_cost,
+        namespace_cost,
+        constructor_cost,
+        ..
+    } = stats;
 
-    let base_fee_cr = base_fee as f64 / 1_000_000.0;
-    let prio_fee_cr = priority_fee.unwrap_or(0) as f64 / 1_000_000.0;
-    let total_fee_cr = base_fee_cr + prio_fee_cr;
+    let prio_fee_cr = stats.priority_fee as f64 / 1_000_000.0;
+    let total_fee_cr = stats.total_fee as f64 / 1_000_000.0;
 
     log::info!(
         "\n{} {}",
@@ -119,7 +474,7 @@ pub fn print_deployment_stats<N: Network>(
         )));
     }
 
-    Ok(())
+    Ok(stats)
 }
 
 /// Pretty-print execution statistics.
@@ -129,13 +484,13 @@ pub fn print_execution_stats<N: Network>(
     execution: &Execution<N>,
     priority_fee: Option<u64>,
     consensus_version: ConsensusVersion,
-) -> Result<()> {
-    let (base_fee, (storage_cost, execution_cost)) =
-        execution_cost(&vm.process().lock(), execution, consensus_version)?;
+) -> Result<ExecutionStats> {
+    let stats = ExecutionStats::new(vm, execution, priority_fee, consensus_version)?;
+    let storage_cost = stats.storage_cost;
+    let execution_cost = stats.execution_cost;
 
-    let base_cr = base_fee as f64 / 1_000_000.0;
-    let prio_cr = priority_fee.unwrap_or(0) as f64 / 1_000_000.0;
-    let total_cr = base_cr + prio_cr;
+    let prio_cr = stats.priority_fee as f64 / 1_000_000.0;
+    let total_cr = stats.total_fee as f64 / 1_000_000.0;
 
     log::info!(
         "\n{} {}",
@@ -172,5 +527,5 @@ pub fn print_execution_stats<N: Network>(
         "{}",
         "──────────────────────────────────────────────".dimmed()
     );
-    Ok(())
+    Ok(stats)
 }
@@ -0,0 +1,732 @@
+use crate::account::Account;
+use crate::address::Address;
+use crate::bundle::AuthorizationBundle;
+use crate::error::{Error, Result};
+use crate::fees::{balance_drop, public_balance};
+use crate::layers::Layer;
+use crate::output::CallOutput;
+use crate::simulation::Simulation;
+use crate::stats::{DeploymentStats, ExecutionStats};
+use crate::vm_manager::VMManager;
+use serde::{Deserialize, Serialize};
+use snarkvm::prelude::{
+    Ciphertext, Group, Identifier, Network, Owner, Plaintext, Program, ProgramID, Record, Value,
+};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+/// One deployment or execution in a transcript.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TranscriptEntry {
+    Deploy {
+        program: String,
+        bytecode: String,
+        dependencies: Vec<String>,
+        caller: String,
+        /// The drop in the caller's public balance, in microcredits.
+        fee: Option<u64>,
+        error: Option<String>,
+    },
+    Execute {
+        program: String,
+        function: String,
+        dependencies: Vec<String>,
+        caller: String,
+        fee_payer: Option<String>,
+        inputs: Vec<TranscriptInput>,
+        outputs: Vec<String>,
+        /// The drop in the fee payer's public balance, in microcredits.
+        ///
+        /// Calls to `credits.aleo` move credits themselves, so their fee is not recorded.
+        fee: Option<u64>,
+        error: Option<String>,
+    },
+}
+
+impl TranscriptEntry {
+    /// The program and function, for reports.
+    pub fn call(&self) -> String {
+        match self {
+            Self::Deploy { program, .. } => format!("deploy {program}"),
+            Self::Execute {
+                program, function, ..
+            } => format!("{program}/{function}"),
+        }
+    }
+}
+
+/// An input of a recorded execution.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TranscriptInput {
+    /// A record returned by an earlier entry, replaced by the replayed record.
+    Output { entry: usize, output: usize },
+    /// An Aleo value, such as `100u64`.
+    Value(String),
+}
+
+/// Read the entries of a JSON-lines transcript.
+pub fn read_transcript(path: impl AsRef<Path>) -> Result<Vec<TranscriptEntry>> {
+    let path = path.as_ref();
+    let file = File::open(path)
+        .map_err(|e| Error::Other(format!("Failed to open {}: {e}", path.display())))?;
+    BufReader::new(file)
+        .lines()
+        .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .map(|line| {
+            let line =
+                line.map_err(|e| Error::Other(format!("Failed to read {}: {e}", path.display())))?;
+            Ok(serde_json::from_str(&line)?)
+        })
+        .collect()
+}
+
+/// Named accounts, so transcripts show `alice` instead of an address.
+struct Labels<N: Network> {
+    accounts: Vec<(String, Account<N>)>,
+}
+
+impl<N: Network> Labels<N> {
+    fn label(&self, address: &Address<N>) -> String {
+        self.accounts
+            .iter()
+            .find(|(_, account)| account.address() == *address)
+            .map(|(label, _)| label.clone())
+            .unwrap_or_else(|| address.to_string())
+    }
+
+    fn account(&self, label: &str) -> Result<&Account<N>> {
+        self.accounts
+            .iter()
+            .find(|(name, account)| name == label || account.address().to_string() == label)
+            .map(|(_, account)| account)
+            .ok_or_else(|| Error::Other(format!("No account for caller '{label}'")))
+    }
+
+    /// Render an output so it compares equal across runs, dropping record nonces.
+    fn render(&self, output: &CallOutput<N>) -> String {
+        match output {
+            CallOutput::Value(Value::Record(record)) => render_record(record),
+            CallOutput::Value(value) => value.to_string(),
+            CallOutput::Encrypted(ciphertext) => match self.decrypt(ciphertext) {
+                Some(record) => render_record(&record),
+                None => "encrypted record".to_string(),
+            },
+            CallOutput::External(_) => "external record".to_string(),
+        }
+    }
+
+    fn decrypt(&self, ciphertext: &Record<N, Ciphertext<N>>) -> Option<Record<N, Plaintext<N>>> {
+        self.accounts
+            .iter()
+            .find(|(_, account)| ciphertext.is_owner(account.view_key()))
+            .and_then(|(_, account)| ciphertext.decrypt(account.view_key()).ok())
+    }
+}
+
+fn render_record<N: Network>(record: &Record<N, Plaintext<N>>) -> String {
+    let owner = match record.owner() {
+        Owner::Public(address) => format!("{address}.public"),
+        Owner::Private(plaintext) => format!("{plaintext}.private"),
+    };
+    let entries: Vec<String> = record
+        .data()
+        .iter()
+        .map(|(name, entry)| format!("{name}: {entry}"))
+        .collect();
+    format!("{{ owner: {owner}, {} }}", entries.join(", "))
+}
+
+fn record_nonce<N: Network>(output: &CallOutput<N>) -> Option<Group<N>> {
+    match output {
+        CallOutput::Value(Value::Record(record)) => Some(*record.nonce()),
+        CallOutput::Encrypted(ciphertext) => Some(*ciphertext.nonce()),
+        _ => None,
+    }
+}
+
+struct TranscriptWriter<N: Network> {
+    file: File,
+    entries: usize,
+    /// Where each record output first appeared, by nonce.
+    records: HashMap<Group<N>, (usize, usize)>,
+    labels: Labels<N>,
+}
+
+impl<N: Network> TranscriptWriter<N> {
+    fn write(&mut self, entry: &TranscriptEntry) -> Result<()> {
+        let line = serde_json::to_string(entry)?;
+        writeln!(self.file, "{line}")
+            .and_then(|_| self.file.flush())
+            .map_err(|e| Error::Other(format!("Failed to write transcript: {e}")))?;
+        self.entries += 1;
+        Ok(())
+    }
+
+    fn input(&self, value: &Value<N>) -> TranscriptInput {
+        match value {
+            Value::Record(record) => match self.records.get(record.nonce()) {
+                Some(&(entry, output)) => TranscriptInput::Output { entry, output },
+                None => TranscriptInput::Value(value.to_string()),
+            },
+            _ => TranscriptInput::Value(value.to_string()),
+        }
+    }
+}
+
+/// Records every deployment and execution through `M` to a JSON-lines transcript.
+///
+/// Replay the transcript against another `VMManager` with `Replayer`.
+pub struct Recording<N: Network, M: VMManager<N>> {
+    inner: M,
+    writer: Arc<Mutex<TranscriptWriter<N>>>,
+}
+
+impl<N: Network, M: VMManager<N>> Clone for Recording<N, M> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            writer: self.writer.clone(),
+        }
+    }
+}
+
+/// Records calls to a JSON-lines transcript, see `Recording`.
+///
+/// Managers layered by the same `RecordLayer` write to the same transcript.
+pub struct RecordLayer<N: Network> {
+    writer: Arc<Mutex<TranscriptWriter<N>>>,
+}
+
+impl<N: Network> Clone for RecordLayer<N> {
+    fn clone(&self) -> Self {
+        Self {
+            writer: self.writer.clone(),
+        }
+    }
+}
+
+impl<N: Network> RecordLayer<N> {
+    /// Record to a new transcript at `path`, replacing an existing one.
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::create(path)
+            .map_err(|e| Error::Other(format!("Failed to create {}: {e}", path.display())))?;
+        Ok(Self {
+            writer: Arc::new(Mutex::new(TranscriptWriter {
+                file,
+                entries: 0,
+                records: HashMap::new(),
+                labels: Labels {
+                    accounts: Vec::new(),
+                },
+            })),
+        })
+    }
+
+    /// Show `account` as `label` in the transcript.
+    ///
+    /// Records owned by labeled accounts are decrypted before they are recorded.
+    pub fn label(self, label: &str, account: &Account<N>) -> Self {
+        self.writer
+            .lock()
+            .unwrap()
+            .labels
+            .accounts
+            .push((label.to_string(), account.clone()));
+        self
+    }
+}
+
+impl<N: Network, M: VMManager<N>> Layer<N, M> for RecordLayer<N> {
+    type Manager = Recording<N, M>;
+
+    fn layer(&self, inner: M) -> Recording<N, M> {
+        Recording {
+            inner,
+            writer: self.writer.clone(),
+        }
+    }
+}
+
+impl<N: Network, M: VMManager<N>> Recording<N, M> {
+    /// Record to a new transcript at `path`, replacing an existing one.
+    pub fn create(inner: M, path: impl AsRef<Path>) -> Result<Self> {
+        Ok(RecordLayer::create(path)?.layer(inner))
+    }
+
+    /// Show `account` as `label` in the transcript, like `RecordLayer::label`.
+    pub fn label(self, label: &str, account: &Account<N>) -> Self {
+        RecordLayer {
+            writer: self.writer.clone(),
+        }
+        .label(label, account);
+        self
+    }
+
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    fn record_execution(
+        &self,
+        (program_id, function_name): (&ProgramID<N>, &Identifier<N>),
+        dependencies: &[ProgramID<N>],
+        caller: &Address<N>,
+        fee_payer: Option<&Address<N>>,
+        inputs: &[Value<N>],
+        result: &Result<Vec<CallOutput<N>>>,
+        fee: Option<u64>,
+    ) -> Result<()> {
+        let mut writer = self.writer.lock().unwrap();
+        let outputs = match result {
+            Ok(outputs) => {
+                for (index, output) in outputs.iter().enumerate() {
+                    if let Some(nonce) = record_nonce(output) {
+                        let entry = writer.entries;
+                        writer.records.entry(nonce).or_insert((entry, index));
+                    }
+                }
+                outputs
+                    .iter()
+                    .map(|output| writer.labels.render(output))
+                    .collect()
+            }
+            Err(_) => Vec::new(),
+        };
+        let entry = TranscriptEntry::Execute {
+            program: program_id.to_string(),
+            function: function_name.to_string(),
+            dependencies: dependencies.iter().map(ToString::to_string).collect(),
+            caller: writer.labels.label(caller),
+            fee_payer: fee_payer.map(|payer| writer.labels.label(payer)),
+            inputs: inputs.iter().map(|input| writer.input(input)).collect(),
+            outputs,
+            fee,
+            error: result.as_ref().err().map(ToString::to_string),
+        };
+        writer.write(&entry)
+    }
+}
+
+impl<N: Network, M: VMManager<N>> VMManager<N> for Recording<N, M> {
+    fn program_exists(&self, program_id: &ProgramID<N>) -> Result<bool> {
+        self.inner.program_exists(program_id)
+    }
+
+    fn program(&self, program_id: &ProgramID<N>) -> Result<Option<Program<N>>> {
+        self.inner.program(program_id)
+    }
+
+    fn mapping_value(
+        &self,
+        program_id: &ProgramID<N>,
+        mapping_name: &Identifier<N>,
+        key: &Value<N>,
+    ) -> Result<Option<Value<N>>> {
+        self.inner.mapping_value(program_id, mapping_name, key)
+    }
+
+    fn evaluate_view(
+        &self,
+        program_id: &ProgramID<N>,
+        view_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+    ) -> Result<Vec<Value<N>>> {
+        self.inner.evaluate_view(program_id, view_name, inputs)
+    }
+
+    fn deploy_and_broadcast(
+        &self,
+        deployer: &Account<N>,
+        program: &Program<N>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<()> {
+        let before = public_balance(&self.inner, &deployer.address());
+        let result = self
+            .inner
+            .deploy_and_broadcast(deployer, program, dependencies);
+        let fee = balance_drop(&self.inner, before, &deployer.address());
+
+        let mut writer = self.writer.lock().unwrap();
+        let entry = TranscriptEntry::Deploy {
+            program: program.id().to_string(),
+            bytecode: program.to_string(),
+            dependencies: dependencies.iter().map(ToString::to_string).collect(),
+            caller: writer.labels.label(&deployer.address()),
+            fee,
+            error: result.as_ref().err().map(ToString::to_string),
+        };
+        writer.write(&entry)?;
+        result
+    }
+
+    fn execute_and_broadcast(
+        &self,
+        account: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<Vec<CallOutput<N>>> {
+        let before = public_balance(&self.inner, &account.address());
+        let result = self.inner.execute_and_broadcast(
+            account,
+            program_id,
+            function_name,
+            inputs.clone(),
+            dependencies,
+        );
+        let fee = match program_id.to_string().as_str() {
+            "credits.aleo" => None,
+            _ => balance_drop(&self.inner, before, &account.address()),
+        };
+        self.record_execution(
+            (program_id, function_name),
+            dependencies,
+            &account.address(),
+            None,
+            &inputs,
+            &result,
+            fee,
+        )?;
+        result
+    }
+
+    fn execute_sponsored(
+        &self,
+        account: &Account<N>,
+        fee_payer: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<Vec<CallOutput<N>>> {
+        let before = public_balance(&self.inner, &fee_payer.address());
+        let result = self.inner.execute_sponsored(
+            account,
+            fee_payer,
+            program_id,
+            function_name,
+            inputs.clone(),
+            dependencies,
+        );
+        let fee = match program_id.to_string().as_str() {
+            "credits.aleo" => None,
+            _ => balance_drop(&self.inner, before, &fee_payer.address()),
+        };
+        self.record_execution(
+            (program_id, function_name),
+            dependencies,
+            &account.address(),
+            Some(&fee_payer.address()),
+            &inputs,
+            &result,
+            fee,
+        )?;
+        result
+    }
+
+    fn simulate(
+        &self,
+        account: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<Simulation<N>> {
+        self.inner
+            .simulate(account, program_id, function_name, inputs, dependencies)
+    }
+
+    fn estimate_execution_fee(
+        &self,
+        account: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<ExecutionStats> {
+        self.inner
+            .estimate_execution_fee(account, program_id, function_name, inputs, dependencies)
+    }
+
+    fn estimate_deployment_fee(
+        &self,
+        deployer: &Account<N>,
+        program: &Program<N>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<DeploymentStats> {
+        self.inner
+            .estimate_deployment_fee(deployer, program, dependencies)
+    }
+
+    fn authorize_call(
+        &self,
+        account: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<AuthorizationBundle<N>> {
+        self.inner
+            .authorize_call(account, program_id, function_name, inputs, dependencies)
+    }
+
+    fn broadcast_authorization(
+        &self,
+        bundle: &AuthorizationBundle<N>,
+    ) -> Result<Vec<CallOutput<N>>> {
+        let request = bundle
+            .authorization()
+            .peek_next()
+            .map_err(|e| Error::Other(format!("Empty authorization: {e}")))?;
+        let signer = Address::from(*request.signer());
+        let result = self.inner.broadcast_authorization(bundle);
+        self.record_execution(
+            (bundle.program_id(), bundle.function_name()),
+            bundle.dependencies(),
+            &signer,
+            None,
+            request.inputs(),
+            &result,
+            None,
+        )?;
+        result
+    }
+}
+
+/// An entry whose replay did not match the transcript.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Divergence {
+    pub entry: usize,
+    pub call: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#{} {}\n  expected: {}\n  actual:   {}",
+            self.entry, self.call, self.expected, self.actual
+        )
+    }
+}
+
+/// The result of replaying a transcript.
+#[derive(Clone, Debug, Default)]
+pub struct ReplayReport {
+    pub entries: usize,
+    pub divergences: Vec<Divergence>,
+}
+
+impl ReplayReport {
+    pub fn is_ok(&self) -> bool {
+        self.divergences.is_empty()
+    }
+}
+
+impl fmt::Display for ReplayReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Replayed {} entries, {} diverged",
+            self.entries,
+            self.divergences.len()
+        )?;
+        for divergence in &self.divergences {
+            write!(f, "\n{divergence}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Re-runs a transcript against a `VMManager` and reports output divergences.
+///
+/// Error messages differ between VMs, so only whether an entry failed is compared.
+pub struct Replayer<N: Network, M: VMManager<N>> {
+    vm: M,
+    labels: Labels<N>,
+}
+
+impl<N: Network, M: VMManager<N>> Replayer<N, M> {
+    pub fn new(vm: M) -> Self {
+        Self {
+            vm,
+            labels: Labels {
+                accounts: Vec::new(),
+            },
+        }
+    }
+
+    /// Sign the entries recorded for `label` with `account`.
+    pub fn label(mut self, label: &str, account: &Account<N>) -> Self {
+        self.labels
+            .accounts
+            .push((label.to_string(), account.clone()));
+        self
+    }
+
+    /// Replay the transcript at `path`.
+    pub fn replay(&self, path: impl AsRef<Path>) -> Result<ReplayReport> {
+        let entries = read_transcript(path)?;
+        let mut report = ReplayReport {
+            entries: entries.len(),
+            divergences: Vec::new(),
+        };
+        let mut outputs: Vec<Vec<CallOutput<N>>> = Vec::new();
+
+        for (index, entry) in entries.iter().enumerate() {
+            let (expected, actual) = match self.replay_entry(entry, &outputs) {
+                Ok((expected, actual, entry_outputs)) => {
+                    outputs.push(entry_outputs);
+                    (expected, actual)
+                }
+                Err(e) => {
+                    outputs.push(Vec::new());
+                    (expected_of(entry), format!("error: {e}"))
+                }
+            };
+            if expected != actual {
+                report.divergences.push(Divergence {
+                    entry: index,
+                    call: entry.call(),
+                    expected,
+                    actual,
+                });
+            }
+        }
+        Ok(report)
+    }
+
+    /// Replay one entry, returning the expected and actual results along with its outputs.
+    fn replay_entry(
+        &self,
+        entry: &TranscriptEntry,
+        outputs: &[Vec<CallOutput<N>>],
+    ) -> Result<(String, String, Vec<CallOutput<N>>)> {
+        match entry {
+            TranscriptEntry::Deploy {
+                bytecode,
+                dependencies,
+                caller,
+                ..
+            } => {
+                let program: Program<N> = bytecode.parse()?;
+                let dependencies = parse_program_ids(dependencies)?;
+                let actual = match self.vm.program(program.id())? {
+                    Some(deployed) if deployed.to_string() == program.to_string() => {
+                        "deployed".to_string()
+                    }
+                    Some(deployed) => format!("deployed different bytecode:\n{deployed}"),
+                    None => match self.vm.deploy_and_broadcast(
+                        self.labels.account(caller)?,
+                        &program,
+                        &dependencies,
+                    ) {
+                        Ok(()) => "deployed".to_string(),
+                        Err(_) => "error".to_string(),
+                    },
+                };
+                Ok((expected_of(entry), actual, Vec::new()))
+            }
+            TranscriptEntry::Execute {
+                program,
+                function,
+                dependencies,
+                caller,
+                fee_payer,
+                inputs,
+                ..
+            } => {
+                let account = self.labels.account(caller)?;
+                let program_id = ProgramID::from_str(program)?;
+                let function_name = Identifier::from_str(function)?;
+                let dependencies = parse_program_ids(dependencies)?;
+                let inputs = inputs
+                    .iter()
+                    .map(|input| self.input(input, account, outputs))
+                    .collect::<Result<Vec<_>>>()?;
+
+                let result = match fee_payer {
+                    Some(fee_payer) => self.vm.execute_sponsored(
+                        account,
+                        self.labels.account(fee_payer)?,
+                        &program_id,
+                        &function_name,
+                        inputs,
+                        &dependencies,
+                    ),
+                    None => self.vm.execute_and_broadcast(
+                        account,
+                        &program_id,
+                        &function_name,
+                        inputs,
+                        &dependencies,
+                    ),
+                };
+                match result {
+                    Ok(entry_outputs) => {
+                        let rendered: Vec<String> = entry_outputs
+                            .iter()
+                            .map(|output| self.labels.render(output))
+                            .collect();
+                        Ok((
+                            expected_of(entry),
+                            format!("[{}]", rendered.join(", ")),
+                            entry_outputs,
+                        ))
+                    }
+                    Err(_) => Ok((expected_of(entry), "error".to_string(), Vec::new())),
+                }
+            }
+        }
+    }
+
+    fn input(
+        &self,
+        input: &TranscriptInput,
+        caller: &Account<N>,
+        outputs: &[Vec<CallOutput<N>>],
+    ) -> Result<Value<N>> {
+        match input {
+            TranscriptInput::Value(value) => Ok(Value::from_str(value)?),
+            TranscriptInput::Output { entry, output } => {
+                match outputs.get(*entry).and_then(|outputs| outputs.get(*output)) {
+                    Some(CallOutput::Value(value)) => Ok(value.clone()),
+                    Some(CallOutput::Encrypted(ciphertext)) => {
+                        Ok(Value::Record(ciphertext.decrypt(caller.view_key())?))
+                    }
+                    Some(CallOutput::External(_)) => Err(Error::Other(format!(
+                        "Output {output} of entry {entry} is an external record of another account"
+                    ))),
+                    None => Err(Error::Other(format!(
+                        "Output {output} of entry {entry} was not replayed"
+                    ))),
+                }
+            }
+        }
+    }
+}
+
+/// The recorded result of `entry`, rendered like a replayed one.
+fn expected_of(entry: &TranscriptEntry) -> String {
+    match entry {
+        TranscriptEntry::Deploy { error: Some(_), .. }
+        | TranscriptEntry::Execute { error: Some(_), .. } => "error".to_string(),
+        TranscriptEntry::Deploy { .. } => "deployed".to_string(),
+        TranscriptEntry::Execute { outputs, .. } => format!("[{}]", outputs.join(", ")),
+    }
+}
+
+fn parse_program_ids<N: Network>(ids: &[String]) -> Result<Vec<ProgramID<N>>> {
+    ids.iter().map(|id| Ok(ProgramID::from_str(id)?)).collect()
+}
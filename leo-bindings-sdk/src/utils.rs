@@ -6,6 +6,7 @@ pub async fn poll_until<F, Fut, T>(
     mut check_fn: F,
     timeout: Duration,
     initial_delay: Duration,
+    max_delay: Duration,
 ) -> Result<T>
 where
     F: FnMut() -> Fut,
@@ -13,7 +14,6 @@ where
 {
     let start = Instant::now();
     let mut delay = initial_delay;
-    let max_delay = Duration::from_secs(5);
 
     loop {
         if start.elapsed() >= timeout {
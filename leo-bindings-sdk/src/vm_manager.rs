@@ -1,23 +1,41 @@
 use crate::account::Account;
+use crate::bundle::AuthorizationBundle;
 use crate::config::Client;
 use crate::error::{Error, Result};
+use crate::fees::{RecordSource, fee_change, microcredits, select_fee_record};
+use crate::fork::Fork;
+use crate::gas::Backend;
+use crate::layers::Layer;
 use crate::local_chain::encode_local_chain_blocks;
-use crate::stats::{print_deployment_stats, print_execution_stats};
+use crate::output::{CallOutput, caller_outputs};
+use crate::pending::PendingTransaction;
+use crate::simulation::{
+    MappingEntries, MappingKeys, MappingWrite, Receipt, Simulation, mapping_writes,
+};
+use crate::stats::{
+    DeploymentStats, ExecutionStats, check_deployment, check_execution, print_deployment_stats,
+    print_execution_stats, record_deployment, record_execution,
+};
 use aleo_std::StorageMode;
 use http::uri::Uri;
-use snarkvm::ledger::block::Transaction;
+use indexmap::IndexSet;
+use snarkvm::ledger::block::{Transaction, Transition};
 use snarkvm::ledger::query::Query;
 use snarkvm::ledger::store::ConsensusStore;
 use snarkvm::ledger::store::helpers::memory::{BlockMemory, ConsensusMemory};
 use snarkvm::prelude::*;
 use snarkvm::synthesizer::VM;
 use snarkvm::synthesizer::program::{FinalizeGlobalState, FinalizeStoreTrait, StackTrait};
+use std::sync::{Arc, Mutex};
 
 pub const CONSENSUS_VERSION: ConsensusVersion = ConsensusVersion::V15;
 
 pub trait VMManager<N: Network>: Send + Sync + Clone {
     fn program_exists(&self, program_id: &ProgramID<N>) -> Result<bool>;
 
+    /// The deployed program `program_id`, if it exists.
+    fn program(&self, program_id: &ProgramID<N>) -> Result<Option<Program<N>>>;
+
     fn mapping_value(
         &self,
         program_id: &ProgramID<N>,
@@ -46,24 +64,90 @@ pub trait VMManager<N: Network>: Send + Sync + Clone {
         function_name: &Identifier<N>,
         inputs: Vec<Value<N>>,
         dependencies: &[ProgramID<N>],
-    ) -> Result<Vec<Value<N>>>;
+    ) -> Result<Vec<CallOutput<N>>>;
+
+    /// Execute a function signed by `account` with the fee paid by `fee_payer`.
+    fn execute_sponsored(
+        &self,
+        account: &Account<N>,
+        fee_payer: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<Vec<CallOutput<N>>>;
+
+    /// Execute and finalize a call against the current state without committing it.
+    fn simulate(
+        &self,
+        account: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<Simulation<N>>;
+
+    /// Estimate the cost of a call, including finalize, without committing or broadcasting it.
+    fn estimate_execution_fee(
+        &self,
+        account: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<ExecutionStats>;
+
+    /// Estimate the cost of deploying `program` without committing or broadcasting it.
+    fn estimate_deployment_fee(
+        &self,
+        deployer: &Account<N>,
+        program: &Program<N>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<DeploymentStats>;
+
+    /// Sign a call and its fee without submitting it.
+    fn authorize_call(
+        &self,
+        account: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<AuthorizationBundle<N>>;
+
+    /// Prove and submit a call signed with `authorize_call`.
+    fn broadcast_authorization(
+        &self,
+        bundle: &AuthorizationBundle<N>,
+    ) -> Result<Vec<CallOutput<N>>>;
+
+    /// Wrap the manager in `layer`.
+    fn layer<L: Layer<N, Self>>(self, layer: L) -> L::Manager {
+        layer.layer(self)
+    }
 }
 
 #[derive(Clone)]
 pub struct NetworkVm<N: Network> {
     vm: VM<N, ConsensusMemory<N>>,
     client: Client,
+    fee_records: Option<Arc<dyn RecordSource<N>>>,
 }
 
 impl<N: Network> std::fmt::Debug for NetworkVm<N> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("NetworkVm")
             .field("client", &self.client)
+            .field("private_fees", &self.fee_records.is_some())
             .finish_non_exhaustive()
     }
 }
 
 impl<N: Network> NetworkVm<N> {
+    /// Create a VM for `client`, without sending any request.
+    ///
+    /// For offline signing the client may point at an unreachable endpoint, see
+    /// `add_program_with_edition` and `authorize_call_offline`.
     pub fn new(client: &Client) -> Result<Self> {
         let store = ConsensusStore::<N, ConsensusMemory<N>>::open(StorageMode::Production)
             .map_err(|e| Error::Other(format!("Failed to create consensus store: {}", e)))?;
@@ -74,9 +158,16 @@ impl<N: Network> NetworkVm<N> {
         Ok(Self {
             vm,
             client: client.clone(),
+            fee_records: None,
         })
     }
 
+    /// Pay fees with `credits.aleo` records from `fee_records` instead of the public balance.
+    pub fn with_fee_records(mut self, fee_records: impl RecordSource<N> + 'static) -> Self {
+        self.fee_records = Some(Arc::new(fee_records));
+        self
+    }
+
     pub fn client(&self) -> &Client {
         &self.client
     }
@@ -88,6 +179,13 @@ impl<N: Network> NetworkVm<N> {
     pub fn add_program(&self, program: &Program<N>) -> Result<()> {
         let program_id = program.id().to_string();
         let edition = crate::block_on(self.client.program_edition::<N>(&program_id))?;
+        self.add_program_with_edition(program, edition)
+    }
+
+    /// Add `program` at a known `edition` without asking the network, for offline signing.
+    ///
+    /// Its imports must be added first.
+    pub fn add_program_with_edition(&self, program: &Program<N>, edition: u16) -> Result<()> {
         self.vm
             .process()
             .lock()
@@ -164,7 +262,14 @@ impl<N: Network> NetworkVm<N> {
         &self,
         authorization: &Authorization<N>,
         view_key: &ViewKey<N>,
-    ) -> Result<Vec<Value<N>>> {
+    ) -> Result<Vec<CallOutput<N>>> {
+        Self::authorization_outputs(authorization, view_key)
+    }
+
+    pub(crate) fn authorization_outputs(
+        authorization: &Authorization<N>,
+        view_key: &ViewKey<N>,
+    ) -> Result<Vec<CallOutput<N>>> {
         let request = authorization
             .peek_next()
             .map_err(|e| Error::Other(format!("Failed to peek authorization: {}", e)))?;
@@ -179,17 +284,35 @@ impl<N: Network> NetworkVm<N> {
         let num_inputs = request.inputs().len();
 
         let transitions = authorization.transitions();
-        let main_transition = transitions
+        let (main_transition, child_transitions) = transitions
             .values()
-            .last()
+            .collect::<Vec<_>>()
+            .split_last()
+            .map(|(main, children)| (*main, children.to_vec()))
             .ok_or_else(|| Error::Other("Authorization contains no transitions".to_string()))?;
 
         main_transition
             .outputs()
             .iter()
             .enumerate()
-            .map(|(i, output)| {
-                Self::decrypt_output(output, i, num_inputs, function_id, request.tvk(), view_key)
+            .map(|(i, output)| match output {
+                Output::ExternalRecord(output_hash) => Self::find_external_record(
+                    output_hash,
+                    &child_transitions,
+                    i,
+                    num_inputs,
+                    function_id,
+                    request.tvk(),
+                    view_key,
+                ),
+                _ => Self::decrypt_output(
+                    output,
+                    i,
+                    num_inputs,
+                    function_id,
+                    request.tvk(),
+                    view_key,
+                ),
             })
             .collect()
     }
@@ -201,10 +324,10 @@ impl<N: Network> NetworkVm<N> {
         function_id: Field<N>,
         tvk: &Field<N>,
         view_key: &ViewKey<N>,
-    ) -> Result<Value<N>> {
+    ) -> Result<CallOutput<N>> {
         match output {
             Output::Constant(_, Some(plaintext)) | Output::Public(_, Some(plaintext)) => {
-                Ok(Value::Plaintext(plaintext.clone()))
+                Ok(CallOutput::Value(Value::Plaintext(plaintext.clone())))
             }
             Output::Private(_, Some(ciphertext)) => {
                 let index = Field::from_u16(
@@ -217,24 +340,74 @@ impl<N: Network> NetworkVm<N> {
                 let plaintext = ciphertext.decrypt_symmetric(output_view_key).map_err(|e| {
                     Error::Other(format!("Failed to decrypt private output: {}", e))
                 })?;
-                Ok(Value::Plaintext(plaintext))
+                Ok(CallOutput::Value(Value::Plaintext(plaintext)))
             }
             Output::Record(_, _, Some(record_ciphertext), _) => {
+                if !record_ciphertext.is_owner(view_key) {
+                    return Ok(CallOutput::Encrypted(record_ciphertext.clone()));
+                }
                 let record_plaintext = record_ciphertext
                     .decrypt(view_key)
                     .map_err(|e| Error::Other(format!("Failed to decrypt record: {}", e)))?;
-                Ok(Value::Record(record_plaintext))
+                Ok(CallOutput::Value(Value::Record(record_plaintext)))
             }
-            Output::Future(_, Some(future)) => Ok(Value::Future(future.clone())),
-            Output::ExternalRecord(_) => Err(Error::Other(
-                "External record outputs are not supported".to_string(),
-            )),
+            Output::Future(_, Some(future)) => Ok(CallOutput::Value(Value::Future(future.clone()))),
             _ => Err(Error::Other(
                 "Output value is missing from transition".to_string(),
             )),
         }
     }
 
+    /// Find the record behind an external record output in the transitions of the called programs.
+    ///
+    /// The external record hash commits to the record plaintext, so only records owned by
+    /// `view_key` can be matched. Other records are returned as their hash.
+    fn find_external_record(
+        output_hash: &Field<N>,
+        child_transitions: &[&Transition<N>],
+        output_index: usize,
+        num_inputs: usize,
+        function_id: Field<N>,
+        tvk: &Field<N>,
+        view_key: &ViewKey<N>,
+    ) -> Result<CallOutput<N>> {
+        let index = Field::from_u16(
+            u16::try_from(num_inputs + output_index)
+                .map_err(|e| Error::Other(format!("Index overflow: {}", e)))?,
+        );
+        let record_ciphertexts = child_transitions
+            .iter()
+            .flat_map(|transition| transition.outputs().iter())
+            .filter_map(|output| match output {
+                Output::Record(_, _, Some(record_ciphertext), _)
+                    if record_ciphertext.is_owner(view_key) =>
+                {
+                    Some(record_ciphertext)
+                }
+                _ => None,
+            });
+        for record_ciphertext in record_ciphertexts {
+            let record = record_ciphertext
+                .decrypt(view_key)
+                .map_err(|e| Error::Other(format!("Failed to decrypt record: {}", e)))?;
+            let value = Value::Record(record);
+            let mut preimage = vec![function_id];
+            preimage.extend(
+                value
+                    .to_fields()
+                    .map_err(|e| Error::Other(format!("Failed to hash record: {}", e)))?,
+            );
+            preimage.push(*tvk);
+            preimage.push(index);
+            let hash = N::hash_psd8(&preimage)
+                .map_err(|e| Error::Other(format!("Failed to hash record: {}", e)))?;
+            if hash == *output_hash {
+                return Ok(CallOutput::Value(value));
+            }
+        }
+        Ok(CallOutput::External(*output_hash))
+    }
+
     pub fn calculate_cost(&self, transaction: &Transaction<N>) -> Result<(u64, (u64, u64))> {
         let execution = transaction
             .execution()
@@ -292,15 +465,84 @@ impl<N: Network> NetworkVm<N> {
         function_name: &Identifier<N>,
         inputs: Vec<Value<N>>,
         dependencies: &[ProgramID<N>],
-    ) -> Result<Vec<Value<N>>> {
+    ) -> Result<Vec<CallOutput<N>>> {
+        self.execute_sponsored(
+            account,
+            account,
+            program_id,
+            function_name,
+            inputs,
+            dependencies,
+        )
+    }
+
+    /// Execute a function signed by `account` with the fee paid by `fee_payer`.
+    pub fn execute_sponsored(
+        &self,
+        account: &Account<N>,
+        fee_payer: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<Vec<CallOutput<N>>> {
+        self.submit_sponsored(
+            account,
+            fee_payer,
+            program_id,
+            function_name,
+            inputs,
+            dependencies,
+        )?
+        .wait()
+    }
+
+    /// Execute a function and broadcast it without waiting for confirmation.
+    pub fn submit(
+        &self,
+        account: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<PendingTransaction<N>> {
+        self.submit_sponsored(
+            account,
+            account,
+            program_id,
+            function_name,
+            inputs,
+            dependencies,
+        )
+    }
+
+    /// Like `execute_sponsored`, without waiting for confirmation.
+    pub fn submit_sponsored(
+        &self,
+        account: &Account<N>,
+        fee_payer: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<PendingTransaction<N>> {
         log::info!("Creating tx: {}.{}", program_id, function_name);
 
         self.ensure_program_loaded(program_id, dependencies)?;
 
-        let balance = crate::block_on(self.client.public_balance::<N>(&account.address()))
-            .map_err(|e| Error::Other(format!("Failed to get balance: {}", e)))?;
+        let balance = match self.fee_records {
+            Some(_) => None,
+            None => Some(
+                crate::block_on(self.client.public_balance::<N>(&fee_payer.address()))
+                    .map_err(|e| e.context("Failed to get balance"))?,
+            ),
+        };
 
-        let (transaction, function_outputs) = if self.client.has_credentials() {
+        let sponsored = fee_payer.address() != account.address();
+        let (transaction, function_outputs, fee_record) = if sponsored || self.fee_records.is_some()
+        {
+            self.execute_with_separate_fee(account, fee_payer, program_id, function_name, inputs)?
+        } else if self.client.has_credentials() {
             let auth = self.authorize(
                 account.private_key(),
                 program_id,
@@ -310,24 +552,36 @@ impl<N: Network> NetworkVm<N> {
             let outputs = self
                 .extract_outputs(&auth, account.view_key())
                 .map_err(|e| Error::Other(format!("Failed to extract outputs: {}", e)))?;
-            let tx = crate::block_on(self.client.prove(&auth))
-                .map_err(|e| Error::Other(format!("Delegated proving failed: {}", e)))?;
+            check_execution(
+                Backend::Network,
+                &self.authorization_stats(program_id, function_name, &auth)?,
+            )?;
+            let tx = crate::block_on(self.client.prove(&auth)).map_err(|e| match e {
+                // The prover may have broadcast the transaction before the request failed.
+                Error::Network(e) if self.client.prover_broadcast() => {
+                    Error::Other(format!("Delegated proving failed: {e}"))
+                }
+                e => e.context("Delegated proving failed"),
+            })?;
             log::info!("✅ Received proved transaction: {}", tx.id());
-            (tx, outputs)
+            (tx, outputs, None)
         } else {
-            self.execute(
-                account.private_key(),
-                program_id,
-                function_name,
-                inputs.clone(),
-                None,
-                0,
-            )
-            .map_err(|e| Error::Other(format!("Failed to execute '{function_name}': {e}")))?
+            let (tx, outputs) = self
+                .execute(
+                    account.private_key(),
+                    program_id,
+                    function_name,
+                    inputs.clone(),
+                    None,
+                    0,
+                )
+                .map_err(|e| Error::Other(format!("Failed to execute '{function_name}': {e}")))?;
+            let outputs = caller_outputs(outputs, &tx, &account.address())?;
+            (tx, outputs, None)
         };
 
         if let Some(execution) = transaction.execution() {
-            print_execution_stats(
+            let stats = print_execution_stats(
                 self.vm(),
                 &program_id.to_string(),
                 execution,
@@ -335,267 +589,1461 @@ impl<N: Network> NetworkVm<N> {
                 CONSENSUS_VERSION,
             )
             .map_err(|e| Error::Other(format!("Failed to print stats: {}", e)))?;
+            // With credentials the cost was checked before proving, as the prover may broadcast.
+            if !self.client.has_credentials() {
+                check_execution(Backend::Network, &stats)?;
+            }
+            record_execution(Backend::Network, stats);
         }
-        let (total_cost, _) = self
-            .calculate_cost(&transaction)
-            .map_err(|e| Error::Other(format!("Failed to calculate cost: {}", e)))?;
-        if balance < total_cost {
-            return Err(Error::Other(format!(
-                "Insufficient balance {balance} for total cost {total_cost} on `{program_id}.{function_name}`"
-            )));
+        if let Some(balance) = balance {
+            let (total_cost, _) = self
+                .calculate_cost(&transaction)
+                .map_err(|e| Error::Other(format!("Failed to calculate cost: {}", e)))?;
+            if balance < total_cost {
+                return Err(Error::Other(format!(
+                    "Insufficient balance {balance} for total cost {total_cost} on `{program_id}.{function_name}`"
+                )));
+            }
         }
 
-        log::info!("📡 Broadcasting tx: {}", transaction.id());
-        crate::block_on(self.client.broadcast_wait(&transaction))
-            .map_err(|e| Error::Other(format!("Failed to broadcast transaction: {}", e)))?;
-
-        Ok(function_outputs)
-    }
-
-    pub fn deploy_and_broadcast(
-        &self,
-        deployer: &Account<N>,
-        program: &Program<N>,
-        dependencies: &[ProgramID<N>],
-    ) -> Result<()> {
-        let program_id = program.id();
-        let program_id_str = program_id.to_string();
-        self.load_missing_dependencies(dependencies)?;
-
-        log::info!("📦 Creating deployment tx for '{}'...", program_id);
-
-        let transaction = self
-            .deploy(deployer.private_key(), program, 0, None)
-            .map_err(|e| Error::Other(format!("Failed to create deployment transaction: {}", e)))?;
-
-        if let Transaction::Deploy(_, _, _, deployment, _fee) = &transaction {
-            print_deployment_stats(
-                self.vm(),
-                &program_id_str,
-                deployment,
-                None,
-                CONSENSUS_VERSION,
-            )
-            .map_err(|e| Error::Other(format!("Failed to print stats: {}", e)))?;
-        }
+        let mut pending = self.submit_execution(transaction, function_outputs)?;
 
-        let balance = crate::block_on(self.client.public_balance::<N>(&deployer.address()))
-            .map_err(|e| Error::Other(format!("Failed to get balance: {}", e)))?;
-        let fee = transaction
-            .fee_amount()
-            .map_err(|e| Error::Other(format!("Failed to get fee: {}", e)))?;
-        if *fee > balance {
-            return Err(Error::Other(format!(
-                "Insufficient balance {} for deployment cost {} on '{}'",
-                balance, fee, program_id
-            )));
+        if let (Some(fee_records), Some(fee_record)) = (&self.fee_records, fee_record) {
+            // The change has no state path until the transaction is accepted.
+            let fee_records = fee_records.clone();
+            let change = fee_change(pending.transaction(), fee_payer);
+            pending = pending.on_accepted(move || fee_records.fee_paid(&fee_record, change));
         }
 
-        log::info!(
-            "📡 Broadcasting deployment tx: {} to {}",
-            transaction.id(),
-            self.client.endpoint()
-        );
-
-        crate::block_on(self.client.broadcast_wait(&transaction))
-            .map_err(|e| Error::Other(format!("Failed to broadcast deployment: {}", e)))?;
-
-        crate::block_on(self.client.wait_for_program::<N>(&program_id_str))?;
-
-        self.add_program(program)
-            .map_err(|e| Error::Other(format!("Failed to add deployed program to VM: {}", e)))?;
-
-        Ok(())
-    }
-
-    fn create_query(endpoint: &str) -> Result<Query<N, BlockMemory<N>>> {
-        let base = endpoint.trim_end_matches('/');
-        let rest_base = if base.ends_with("/v2") {
-            base.to_string()
-        } else {
-            format!("{base}/v2")
-        };
-        let uri: Uri = rest_base
-            .parse()
-            .map_err(|e| Error::Config(format!("Invalid endpoint URI: {}", e)))?;
-        Ok(Query::<N, BlockMemory<N>>::from(uri))
-    }
-}
-
-impl<N: Network> VMManager<N> for NetworkVm<N> {
-    fn program_exists(&self, program_id: &ProgramID<N>) -> Result<bool> {
-        crate::block_on(self.client.program_exists::<N>(&program_id.to_string()))
+        Ok(pending)
     }
 
-    fn mapping_value(
+    /// Estimate the cost of a call, including finalize, without committing or broadcasting it.
+    pub fn estimate_execution_fee(
         &self,
+        account: &Account<N>,
         program_id: &ProgramID<N>,
-        mapping_name: &Identifier<N>,
-        key: &Value<N>,
-    ) -> Result<Option<Value<N>>> {
-        crate::block_on(self.client.mapping::<N>(
-            &program_id.to_string(),
-            &mapping_name.to_string(),
-            key,
-        ))
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<ExecutionStats> {
+        self.ensure_program_loaded(program_id, dependencies)?;
+
+        let authorization =
+            self.authorize(account.private_key(), program_id, function_name, inputs)?;
+        self.authorization_stats(program_id, function_name, &authorization)
     }
 
-    fn evaluate_view(
+    /// The cost of a signed call, known before it is proven.
+    fn authorization_stats(
         &self,
         program_id: &ProgramID<N>,
-        view_name: &Identifier<N>,
-        inputs: Vec<Value<N>>,
-    ) -> Result<Vec<Value<N>>> {
-        crate::block_on(self.client.evaluate_view::<N>(
-            &program_id.to_string(),
-            &view_name.to_string(),
-            &inputs,
-        ))
+        function_name: &Identifier<N>,
+        authorization: &Authorization<N>,
+    ) -> Result<ExecutionStats> {
+        let (base_fee, (storage_cost, execution_cost)) = execution_cost_for_authorization(
+            &self.vm.process().lock(),
+            authorization,
+            CONSENSUS_VERSION,
+        )
+        .map_err(|e| Error::Other(format!("Failed to calculate execution cost: {}", e)))?;
+        Ok(ExecutionStats {
+            program: program_id.to_string(),
+            function: function_name.to_string(),
+            storage_cost,
+            execution_cost,
+            priority_fee: 0,
+            total_fee: base_fee,
+        })
     }
 
-    fn deploy_and_broadcast(
+    /// Estimate the cost of deploying `program` without committing or broadcasting it.
+    pub fn estimate_deployment_fee(
         &self,
-        deployer: &Account<N>,
+        _deployer: &Account<N>,
         program: &Program<N>,
         dependencies: &[ProgramID<N>],
-    ) -> Result<()> {
-        NetworkVm::deploy_and_broadcast(self, deployer, program, dependencies)
+    ) -> Result<DeploymentStats> {
+        self.load_missing_dependencies(dependencies)?;
+
+        // Only the deployment is built, so no fee is authorized or proven.
+        let deployment = self
+            .vm()
+            .deploy_raw(program, &mut rand::rng())
+            .map_err(|e| Error::Other(format!("deploy_raw: {e}")))?;
+        DeploymentStats::new(
+            self.vm(),
+            &program.id().to_string(),
+            &deployment,
+            None,
+            CONSENSUS_VERSION,
+        )
+        .map_err(|e| Error::Other(format!("Failed to calculate deployment cost: {}", e)))
     }
 
-    fn execute_and_broadcast(
+    /// Sign a call and its fee without proving or broadcasting it.
+    pub fn authorize_call(
         &self,
         account: &Account<N>,
         program_id: &ProgramID<N>,
         function_name: &Identifier<N>,
         inputs: Vec<Value<N>>,
         dependencies: &[ProgramID<N>],
-    ) -> Result<Vec<Value<N>>> {
-        NetworkVm::execute_and_broadcast(
-            self,
+    ) -> Result<AuthorizationBundle<N>> {
+        self.ensure_program_loaded(program_id, dependencies)?;
+        let call_inputs = inputs.clone();
+        self.authorize_bundle(
             account,
             program_id,
             function_name,
             inputs,
             dependencies,
+            |base_fee, execution_id| {
+                let (fee_authorization, _) =
+                    self.authorize_fee(account, base_fee, execution_id, &call_inputs)?;
+                Ok(fee_authorization)
+            },
         )
     }
-}
 
-#[derive(Clone)]
-pub struct LocalVM {
-    vm: VM<TestnetV0, ConsensusMemory<TestnetV0>>,
-}
-
-impl std::fmt::Debug for LocalVM {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("LocalVM").finish_non_exhaustive()
+    /// Sign a call and its fee without any request to the network.
+    ///
+    /// The program and its imports must have been added with `add_program_with_edition`.
+    /// The fee is paid with `fee_record` if given, otherwise from the public balance.
+    pub fn authorize_call_offline(
+        &self,
+        account: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+        fee_record: Option<Record<N, Plaintext<N>>>,
+    ) -> Result<AuthorizationBundle<N>> {
+        if !self.contains_program(program_id) {
+            return Err(Error::Other(format!(
+                "Program '{program_id}' is not loaded, add it with add_program_with_edition"
+            )));
+        }
+        self.authorize_bundle(
+            account,
+            program_id,
+            function_name,
+            inputs,
+            dependencies,
+            |base_fee, execution_id| {
+                self.authorize_fee_with(account, base_fee, execution_id, fee_record)
+            },
+        )
     }
-}
+
+    fn authorize_bundle(
+        &self,
+        account: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+        authorize_fee: impl FnOnce(u64, Field<N>) -> Result<Authorization<N>>,
+    ) -> Result<AuthorizationBundle<N>> {
+        let authorization =
+            self.authorize(account.private_key(), program_id, function_name, inputs)?;
+        let outputs = self
+            .extract_outputs(&authorization, account.view_key())
+            .map_err(|e| Error::Other(format!("Failed to extract outputs: {}", e)))?;
+        let (base_fee, _) = execution_cost_for_authorization(
+            &self.vm.process().lock(),
+            &authorization,
+            CONSENSUS_VERSION,
+        )
+        .map_err(|e| Error::Other(format!("Failed to calculate execution cost: {}", e)))?;
+        let execution_id = authorization
+            .to_execution_id()
+            .map_err(|e| Error::Other(format!("Failed to compute execution ID: {}", e)))?;
+        let fee_authorization = authorize_fee(base_fee, execution_id)?;
+
+        Ok(AuthorizationBundle::new(
+            *program_id,
+            *function_name,
+            dependencies.to_vec(),
+            authorization,
+            fee_authorization,
+            outputs,
+        ))
+    }
+
+    /// Prove and broadcast a signed call.
+    pub fn broadcast_authorization(
+        &self,
+        bundle: &AuthorizationBundle<N>,
+    ) -> Result<Vec<CallOutput<N>>> {
+        self.submit_authorization(bundle)?.wait()
+    }
+
+    /// Prove and broadcast a signed call without waiting for confirmation.
+    pub fn submit_authorization(
+        &self,
+        bundle: &AuthorizationBundle<N>,
+    ) -> Result<PendingTransaction<N>> {
+        let program_id = bundle.program_id();
+        self.ensure_program_loaded(program_id, bundle.dependencies())?;
+        // Checked before proving, as a delegated prover may broadcast the transaction.
+        check_execution(
+            Backend::Network,
+            &self.authorization_stats(program_id, bundle.function_name(), bundle.authorization())?,
+        )?;
+
+        let transaction = if self.client.has_credentials() {
+            let tx = crate::block_on(
+                self.client
+                    .prove_with_fee(bundle.authorization(), bundle.fee_authorization()),
+            )
+            .map_err(|e| Error::Other(format!("Delegated proving failed: {}", e)))?;
+            log::info!("✅ Received proved transaction: {}", tx.id());
+            tx
+        } else {
+            let query = Self::create_query(&self.client.endpoint)?;
+            let rng = &mut rand::rng();
+            self.vm
+                .execute_authorization(
+                    bundle.authorization().clone(),
+                    Some(bundle.fee_authorization().clone()),
+                    Some(&query),
+                    rng,
+                )
+                .map_err(|e| Error::Other(format!("Failed to create execution transaction: {e}")))?
+        };
+
+        if let Some(execution) = transaction.execution() {
+            let stats = print_execution_stats(
+                self.vm(),
+                &program_id.to_string(),
+                execution,
+                None,
+                CONSENSUS_VERSION,
+            )
+            .map_err(|e| Error::Other(format!("Failed to print stats: {}", e)))?;
+            record_execution(Backend::Network, stats);
+        }
+
+        self.submit_execution(transaction, bundle.outputs().to_vec())
+    }
+
+    /// Prove and broadcast signed calls concurrently, without waiting for confirmation.
+    ///
+    /// Each call is broadcast as soon as it is proven and tracked on its own,
+    /// so one failing call does not affect the others. The results are in the
+    /// same order as `bundles`.
+    pub fn submit_batch(
+        &self,
+        bundles: &[AuthorizationBundle<N>],
+    ) -> Vec<Result<PendingTransaction<N>>> {
+        let threads = std::thread::available_parallelism().map_or(4, |n| n.get());
+        let mut results = Vec::with_capacity(bundles.len());
+        for chunk in bundles.chunks(threads) {
+            std::thread::scope(|scope| {
+                let handles: Vec<_> = chunk
+                    .iter()
+                    .map(|bundle| scope.spawn(|| self.submit_authorization(bundle)))
+                    .collect();
+                for handle in handles {
+                    results.push(handle.join().unwrap_or_else(|_| {
+                        Err(Error::Other("Proving thread panicked".to_string()))
+                    }));
+                }
+            });
+        }
+        results
+    }
+
+    /// Execute a function signed by `account` with a separate fee authorization from `fee_payer`.
+    fn execute_with_separate_fee(
+        &self,
+        account: &Account<N>,
+        fee_payer: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+    ) -> Result<(
+        Transaction<N>,
+        Vec<CallOutput<N>>,
+        Option<Record<N, Plaintext<N>>>,
+    )> {
+        let authorization = self.authorize(
+            account.private_key(),
+            program_id,
+            function_name,
+            inputs.clone(),
+        )?;
+        let outputs = self
+            .extract_outputs(&authorization, account.view_key())
+            .map_err(|e| Error::Other(format!("Failed to extract outputs: {}", e)))?;
+
+        if self.client.has_credentials() {
+            let stats = self.authorization_stats(program_id, function_name, &authorization)?;
+            check_execution(Backend::Network, &stats)?;
+            let base_fee = stats.total_fee;
+            let execution_id = authorization
+                .to_execution_id()
+                .map_err(|e| Error::Other(format!("Failed to compute execution ID: {}", e)))?;
+            let (fee_authorization, fee_record) =
+                self.authorize_fee(fee_payer, base_fee, execution_id, &inputs)?;
+            let tx = crate::block_on(
+                self.client
+                    .prove_with_fee(&authorization, &fee_authorization),
+            )
+            .map_err(|e| Error::Other(format!("Delegated proving failed: {}", e)))?;
+            log::info!("✅ Received proved transaction: {}", tx.id());
+            return Ok((tx, outputs, fee_record));
+        }
+
+        let query = Self::create_query(&self.client.endpoint)?;
+        let rng = &mut rand::rng();
+        let execution = self
+            .vm
+            .execute_authorization(authorization, None, Some(&query), rng)
+            .map_err(|e| Error::Other(format!("Failed to create execution transaction: {e}")))?
+            .execution()
+            .cloned()
+            .ok_or_else(|| Error::Other("Transaction has no execution".to_string()))?;
+        let (base_fee, _) =
+            execution_cost(&self.vm.process().lock(), &execution, CONSENSUS_VERSION)
+                .map_err(|e| Error::Other(format!("Failed to calculate execution cost: {}", e)))?;
+        let execution_id = execution
+            .to_execution_id()
+            .map_err(|e| Error::Other(format!("Failed to compute execution ID: {}", e)))?;
+        let (fee_authorization, fee_record) =
+            self.authorize_fee(fee_payer, base_fee, execution_id, &inputs)?;
+        let fee = self
+            .vm
+            .execute_fee_authorization(fee_authorization, Some(&query), rng)
+            .map_err(|e| Error::Other(format!("Failed to create fee: {e}")))?;
+        let tx = Transaction::from_execution(execution, Some(fee))
+            .map_err(|e| Error::Other(format!("Failed to create execution transaction: {e}")))?;
+        Ok((tx, outputs, fee_record))
+    }
+
+    /// Authorize a fee of `base_fee` from `fee_payer`, paid with the smallest sufficient
+    /// record from `fee_records` that the call does not spend if set, otherwise from the
+    /// public balance.
+    fn authorize_fee(
+        &self,
+        fee_payer: &Account<N>,
+        base_fee: u64,
+        execution_id: Field<N>,
+        inputs: &[Value<N>],
+    ) -> Result<(Authorization<N>, Option<Record<N, Plaintext<N>>>)> {
+        let fee_record = match &self.fee_records {
+            Some(fee_records) => Some(select_fee_record(
+                fee_records.unspent_credits(fee_payer)?,
+                base_fee,
+                inputs,
+            )?),
+            None => None,
+        };
+        let fee_authorization =
+            self.authorize_fee_with(fee_payer, base_fee, execution_id, fee_record.clone())?;
+        Ok((fee_authorization, fee_record))
+    }
+
+    /// Authorize a fee of `base_fee` from `fee_payer`, paid with `fee_record` if given.
+    fn authorize_fee_with(
+        &self,
+        fee_payer: &Account<N>,
+        base_fee: u64,
+        execution_id: Field<N>,
+        fee_record: Option<Record<N, Plaintext<N>>>,
+    ) -> Result<Authorization<N>> {
+        let rng = &mut rand::rng();
+        match fee_record {
+            Some(fee_record) => self.vm.authorize_fee_private(
+                fee_payer.private_key(),
+                fee_record,
+                base_fee,
+                0,
+                execution_id,
+                rng,
+            ),
+            None => {
+                self.vm
+                    .authorize_fee_public(fee_payer.private_key(), base_fee, 0, execution_id, rng)
+            }
+        }
+        .map_err(|e| Error::Other(format!("Failed to authorize fee: {e}")))
+    }
+
+    pub fn deploy_and_broadcast(
+        &self,
+        deployer: &Account<N>,
+        program: &Program<N>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<()> {
+        let program_id = program.id();
+        let program_id_str = program_id.to_string();
+        self.load_missing_dependencies(dependencies)?;
+
+        log::info!("📦 Creating deployment tx for '{}'...", program_id);
+
+        let mut transaction = self
+            .deploy(deployer.private_key(), program, 0, None)
+            .map_err(|e| Error::Other(format!("Failed to create deployment transaction: {}", e)))?;
+        let fee = transaction
+            .fee_amount()
+            .map_err(|e| Error::Other(format!("Failed to get fee: {}", e)))?;
+
+        // The deployment cost is only known after the deployment is built, so pay it privately by rebuilding.
+        let fee_record = match &self.fee_records {
+            Some(fee_records) => {
+                let fee_record =
+                    select_fee_record(fee_records.unspent_credits(deployer)?, *fee, &[])?;
+                transaction = self
+                    .deploy(deployer.private_key(), program, 0, Some(fee_record.clone()))
+                    .map_err(|e| {
+                        Error::Other(format!("Failed to create deployment transaction: {}", e))
+                    })?;
+                Some(fee_record)
+            }
+            None => None,
+        };
+
+        if let Transaction::Deploy(_, _, _, deployment, _fee) = &transaction {
+            let stats = print_deployment_stats(
+                self.vm(),
+                &program_id_str,
+                deployment,
+                None,
+                CONSENSUS_VERSION,
+            )
+            .map_err(|e| Error::Other(format!("Failed to print stats: {}", e)))?;
+            check_deployment(Backend::Network, &stats)?;
+            record_deployment(Backend::Network, stats);
+        }
+
+        if fee_record.is_none() {
+            let balance = crate::block_on(self.client.public_balance::<N>(&deployer.address()))
+                .map_err(|e| e.context("Failed to get balance"))?;
+            if *fee > balance {
+                return Err(Error::Other(format!(
+                    "Insufficient balance {} for deployment cost {} on '{}'",
+                    balance, fee, program_id
+                )));
+            }
+        }
+
+        log::info!(
+            "📡 Broadcasting deployment tx: {} to {}",
+            transaction.id(),
+            self.client.endpoint()
+        );
+
+        crate::block_on(self.client.broadcast_wait(&transaction))
+            .map_err(|e| Error::Other(format!("Failed to broadcast deployment: {}", e)))?;
+
+        if let (Some(fee_records), Some(fee_record)) = (&self.fee_records, &fee_record) {
+            fee_records.fee_paid(fee_record, fee_change(&transaction, deployer));
+        }
+
+        crate::block_on(self.client.wait_for_program::<N>(&program_id_str))?;
+
+        self.add_program(program)
+            .map_err(|e| Error::Other(format!("Failed to add deployed program to VM: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Broadcast an execution, unless the delegated prover already broadcast it.
+    fn submit_execution(
+        &self,
+        transaction: Transaction<N>,
+        outputs: Vec<CallOutput<N>>,
+    ) -> Result<PendingTransaction<N>> {
+        if self.client.has_credentials() && self.client.prover_broadcast() {
+            log::info!("⏳ Tx broadcast by the prover: {}", transaction.id());
+        } else {
+            log::info!("📡 Broadcasting tx: {}", transaction.id());
+            crate::block_on(self.client.broadcast(&transaction))
+                .map_err(|e| Error::Other(format!("Failed to broadcast transaction: {}", e)))?;
+        }
+        Ok(PendingTransaction::new(
+            self.client.clone(),
+            transaction,
+            outputs,
+        ))
+    }
+
+    pub(crate) fn create_query(endpoint: &str) -> Result<Query<N, BlockMemory<N>>> {
+        let base = endpoint.trim_end_matches('/');
+        let rest_base = if base.ends_with("/v2") {
+            base.to_string()
+        } else {
+            format!("{base}/v2")
+        };
+        let uri: Uri = rest_base
+            .parse()
+            .map_err(|e| Error::Config(format!("Invalid endpoint URI: {}", e)))?;
+        Ok(Query::<N, BlockMemory<N>>::from(uri))
+    }
+}
+
+impl<N: Network> VMManager<N> for NetworkVm<N> {
+    fn program_exists(&self, program_id: &ProgramID<N>) -> Result<bool> {
+        crate::block_on(self.client.program_exists::<N>(&program_id.to_string()))
+    }
+
+    fn program(&self, program_id: &ProgramID<N>) -> Result<Option<Program<N>>> {
+        match crate::block_on(self.client.program::<N>(&program_id.to_string())) {
+            Ok(source) => Ok(Some(source.parse()?)),
+            Err(Error::NotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn mapping_value(
+        &self,
+        program_id: &ProgramID<N>,
+        mapping_name: &Identifier<N>,
+        key: &Value<N>,
+    ) -> Result<Option<Value<N>>> {
+        crate::block_on(self.client.mapping::<N>(
+            &program_id.to_string(),
+            &mapping_name.to_string(),
+            key,
+        ))
+    }
+
+    fn evaluate_view(
+        &self,
+        program_id: &ProgramID<N>,
+        view_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+    ) -> Result<Vec<Value<N>>> {
+        crate::block_on(self.client.evaluate_view::<N>(
+            &program_id.to_string(),
+            &view_name.to_string(),
+            &inputs,
+        ))
+    }
+
+    fn deploy_and_broadcast(
+        &self,
+        deployer: &Account<N>,
+        program: &Program<N>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<()> {
+        NetworkVm::deploy_and_broadcast(self, deployer, program, dependencies)
+    }
+
+    fn execute_and_broadcast(
+        &self,
+        account: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<Vec<CallOutput<N>>> {
+        NetworkVm::execute_and_broadcast(
+            self,
+            account,
+            program_id,
+            function_name,
+            inputs,
+            dependencies,
+        )
+    }
+
+    fn execute_sponsored(
+        &self,
+        account: &Account<N>,
+        fee_payer: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<Vec<CallOutput<N>>> {
+        NetworkVm::execute_sponsored(
+            self,
+            account,
+            fee_payer,
+            program_id,
+            function_name,
+            inputs,
+            dependencies,
+        )
+    }
+
+    fn simulate(
+        &self,
+        _account: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        _inputs: Vec<Value<N>>,
+        _dependencies: &[ProgramID<N>],
+    ) -> Result<Simulation<N>> {
+        Err(Error::Other(format!(
+            "NetworkVm cannot simulate {program_id}/{function_name}, simulate on LocalVM::fork instead"
+        )))
+    }
+
+    fn estimate_execution_fee(
+        &self,
+        account: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<ExecutionStats> {
+        NetworkVm::estimate_execution_fee(
+            self,
+            account,
+            program_id,
+            function_name,
+            inputs,
+            dependencies,
+        )
+    }
+
+    fn estimate_deployment_fee(
+        &self,
+        deployer: &Account<N>,
+        program: &Program<N>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<DeploymentStats> {
+        NetworkVm::estimate_deployment_fee(self, deployer, program, dependencies)
+    }
+
+    fn authorize_call(
+        &self,
+        account: &Account<N>,
+        program_id: &ProgramID<N>,
+        function_name: &Identifier<N>,
+        inputs: Vec<Value<N>>,
+        dependencies: &[ProgramID<N>],
+    ) -> Result<AuthorizationBundle<N>> {
+        NetworkVm::authorize_call(
+            self,
+            account,
+            program_id,
+            function_name,
+            inputs,
+            dependencies,
+        )
+    }
+
+    fn broadcast_authorization(
+        &self,
+        bundle: &AuthorizationBundle<N>,
+    ) -> Result<Vec<CallOutput<N>>> {
+        NetworkVm::broadcast_authorization(self, bundle)
+    }
+}
+
+#[derive(Clone)]
+pub struct LocalVM {
+    vm: VM<TestnetV0, ConsensusMemory<TestnetV0>>,
+    private_fees: bool,
+    fork: Option<Arc<Fork>>,
+    last_receipt: Arc<Mutex<Option<Receipt<TestnetV0>>>>,
+}
+
+impl std::fmt::Debug for LocalVM {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LocalVM")
+            .field("private_fees", &self.private_fees)
+            .field("fork_height", &self.fork_height())
+            .finish_non_exhaustive()
+    }
+}
 
 impl LocalVM {
     pub fn new() -> Result<Self> {
         let bytes = crate::local_chain::load_or_create_local_chain_bytes()?;
         let blocks = crate::local_chain::parse_local_chain_blocks(&bytes)?;
         let vm = crate::local_chain::vm_from_local_chain_blocks(&blocks)?;
-        Ok(Self { vm })
+        Ok(Self {
+            vm,
+            private_fees: false,
+            fork: None,
+            last_receipt: Arc::default(),
+        })
+    }
+
+    /// Fork the state of the node behind `client`, starting at its latest block.
+    ///
+    /// Programs and mapping values are pulled from the node the first time they are used,
+    /// and everything executed afterwards only changes the local state. The fork is not
+    /// consistent at one height: each value is read at the node's latest height when first
+    /// used, which `fork_reads` lists. Use `fork_at` to reject reads past a height.
+    pub fn fork(client: &Client) -> Result<Self> {
+        let height = crate::block_on(client.height::<TestnetV0>())?;
+        Self::fork_with(client, height, false)
+    }
+
+    /// Fork the state of the node behind `client` at `height`.
+    ///
+    /// The node only serves the latest mapping values, so reading a value after the
+    /// node moved past `height` returns an error instead of a value from a later block.
+    pub fn fork_at(client: &Client, height: u32) -> Result<Self> {
+        Self::fork_with(client, height, true)
+    }
+
+    fn fork_with(client: &Client, height: u32, pinned: bool) -> Result<Self> {
+        let mut vm = Self::new()?;
+        vm.fork = Some(Arc::new(Fork::new(client.clone(), height, pinned)));
+        log::info!("🍴 Forked {} at height {height}", client.endpoint());
+        Ok(vm)
+    }
+
+    /// Let finalize blocks read `mapping_name` of `program_id` with keys they compute.
+    ///
+    /// A fork pulls the keys finalize reads from its inputs, but a key computed inside
+    /// finalize cannot be known beforehand and fails the call. After seeding such keys
+    /// with `mapping_value` or `set_mapping_value`, trust the mapping to read them locally.
+    pub fn trust_fork_mapping(
+        &self,
+        program_id: &ProgramID<TestnetV0>,
+        mapping_name: &Identifier<TestnetV0>,
+    ) -> Result<()> {
+        match &self.fork {
+            Some(fork) => {
+                fork.trust_mapping(*program_id, *mapping_name);
+                Ok(())
+            }
+            None => Err(Error::Other("LocalVM is not a fork".to_string())),
+        }
+    }
+
+    /// The remote height this VM was forked at, if it is a fork.
+    pub fn fork_height(&self) -> Option<u32> {
+        self.fork.as_ref().map(|fork| fork.height())
+    }
+
+    /// The mapping keys this fork pulled from the remote, with the height each was read at.
+    pub fn fork_reads(
+        &self,
+    ) -> Vec<(
+        ProgramID<TestnetV0>,
+        Identifier<TestnetV0>,
+        Plaintext<TestnetV0>,
+        u32,
+    )> {
+        self.fork.as_ref().map_or_else(Vec::new, |fork| {
+            fork.reads()
+                .into_iter()
+                .map(|((program_id, mapping_name, key), height)| {
+                    (program_id, mapping_name, key, height)
+                })
+                .collect()
+        })
+    }
+
+    /// Deploy the remote program `program_id` and its imports locally, if missing.
+    ///
+    /// Returns false if the program exists neither locally nor remotely.
+    fn pull_program(&self, fork: &Fork, program_id: &ProgramID<TestnetV0>) -> Result<bool> {
+        if self.contains_program(program_id) {
+            return Ok(true);
+        }
+        let source =
+            match crate::block_on(fork.client().program::<TestnetV0>(&program_id.to_string())) {
+                Ok(source) => source,
+                Err(Error::NotFound(_)) => return Ok(false),
+                Err(e) => return Err(e),
+            };
+        let program = source.parse::<Program<TestnetV0>>()?;
+        let imports: Vec<ProgramID<TestnetV0>> = program.imports().keys().copied().collect();
+        for import in &imports {
+            if !self.pull_program(fork, import)? {
+                return Err(Error::Other(format!(
+                    "Fork: import '{import}' of '{program_id}' not found"
+                )));
+            }
+        }
+
+        log::info!("🍴 Fork: pulling program '{program_id}'");
+        let deployer = Account::dev_account(0).map_err(|e| Error::Other(e.to_string()))?;
+        self.deploy_and_broadcast(&deployer, &program, &imports)?;
+        Ok(true)
+    }
+
+    /// Pull the remote state `transaction` reads before it is committed.
+    fn pull_finalize_state(&self, transaction: &Transaction<TestnetV0>) -> Result<()> {
+        match &self.fork {
+            Some(fork) => fork.pull_finalize_state(&self.vm, transaction),
+            None => Ok(()),
+        }
+    }
+
+    /// Pay fees with the signer's unspent `credits.aleo` records instead of the public balance.
+    pub fn with_private_fees(mut self) -> Self {
+        self.private_fees = true;
+        self
+    }
+
+    /// Pick the record paying a fee of `base_fee`, if private fees are enabled.
+    ///
+    /// `base_fee` is priced with the proof included, so it covers the proofless fee.
+    fn private_fee_record(
+        &self,
+        account: &Account<TestnetV0>,
+        base_fee: impl FnOnce() -> Result<u64>,
+        inputs: &[Value<TestnetV0>],
+    ) -> Result<Option<Record<TestnetV0, Plaintext<TestnetV0>>>> {
+        if !self.private_fees {
+            return Ok(None);
+        }
+        select_fee_record(self.unspent_credits(account)?, base_fee()?, inputs).map(Some)
+    }
+
+    pub fn vm(&self) -> &VM<TestnetV0, ConsensusMemory<TestnetV0>> {
+        &self.vm
+    }
+
+    /// Check the cost of a transaction against the gas snapshot and add it to the cost report.
+    fn record_stats(&self, transaction: &Transaction<TestnetV0>) -> Result<()> {
+        if let Some(deployment) = transaction.deployment() {
+            match DeploymentStats::new(
+                &self.vm,
+                &deployment.program_id().to_string(),
+                deployment,
+                None,
+                CONSENSUS_VERSION,
+            ) {
+                Ok(stats) => {
+                    check_deployment(Backend::Local, &stats)?;
+                    record_deployment(Backend::Local, stats);
+                }
+                Err(e) => log::debug!("Failed to compute stats for {}: {e}", transaction.id()),
+            }
+        } else if let Some(execution) = transaction.execution() {
+            match ExecutionStats::new(&self.vm, execution, None, CONSENSUS_VERSION) {
+                Ok(stats) => {
+                    check_execution(Backend::Local, &stats)?;
+                    record_execution(Backend::Local, stats);
+                }
+                Err(e) => log::debug!("Failed to compute stats for {}: {e}", transaction.id()),
+            }
+        }
+        Ok(())
+    }
+
+    fn contains_program(&self, program_id: &ProgramID<TestnetV0>) -> bool {
+        self.vm.process().contains_program(program_id)
+    }
+
+    fn ensure_program_loaded(
+        &self,
+        program_id: &ProgramID<TestnetV0>,
+        dependencies: &[ProgramID<TestnetV0>],
+    ) -> Result<()> {
+        if let Some(fork) = &self.fork {
+            self.pull_program(fork, program_id)?;
+        }
+        if self.contains_program(program_id) {
+            return Ok(());
+        }
+
+        for dep_id in dependencies {
+            if !self.contains_program(dep_id) {
+                return Err(Error::Other(format!(
+                    "LocalVM: dependency '{dep_id}' not on ledger; deploy it first (missing program '{program_id}')"
+                )));
+            }
+        }
+
+        Err(Error::Other(format!(
+            "LocalVM: program '{program_id}' not loaded; deploy via bindings::new first"
+        )))
+    }
+
+    pub fn deploy_and_broadcast(
+        &self,
+        deployer: &Account<TestnetV0>,
+        program: &Program<TestnetV0>,
+        dependencies: &[ProgramID<TestnetV0>],
+    ) -> Result<()> {
+        let program_id = program.id();
+
+        for dep_id in dependencies {
+            if !self.contains_program(dep_id) {
+                return Err(Error::Other(format!(
+                    "LocalVM: missing dependency '{dep_id}' before deploying '{program_id}'"
+                )));
+            }
+        }
+
+        log::info!("📦 Deploy: creating proofless deployment tx for '{program_id}'");
+
+        let mut rng = rand::rng();
+        let fee_record = self.private_fee_record(
+            deployer,
+            || {
+                let deployment = self
+                    .vm
+                    .deploy_raw(program, &mut rand::rng())
+                    .map_err(|e| Error::Other(format!("deploy_raw: {e}")))?;
+                let (base_fee, _) =
+                    deployment_cost(&self.vm.process().lock(), &deployment, CONSENSUS_VERSION)
+                        .map_err(|e| Error::Other(format!("deployment_cost: {e}")))?;
+                Ok(base_fee)
+            },
+            &[],
+        )?;
+        let transaction = self
+            .vm
+            .deploy_local_proofless(
+                deployer.private_key(),
+                program,
+                fee_record,
+                0,
+                None,
+                &mut rng,
+            )
+            .map_err(|e| Error::Other(format!("deploy_local_proofless: {e}")))?;
+        self.record_stats(&transaction)?;
+
+        let beacon_account = Account::dev_account(0).map_err(|e| Error::Other(e.to_string()))?;
+        let beacon_key = *beacon_account.private_key();
+        crate::local_chain::commit_transaction(&self.vm, &beacon_key, &transaction, &mut rng)?;
+
+        Ok(())
+    }
+
+    pub fn execute_and_broadcast(
+        &self,
+        account: &Account<TestnetV0>,
+        program_id: &ProgramID<TestnetV0>,
+        function_name: &Identifier<TestnetV0>,
+        inputs: Vec<Value<TestnetV0>>,
+        dependencies: &[ProgramID<TestnetV0>],
+    ) -> Result<Vec<CallOutput<TestnetV0>>> {
+        self.execute_with_receipt(account, program_id, function_name, inputs, dependencies)
+            .map(|receipt| receipt.outputs)
+    }
+
+    /// Execute a call, returning its outputs together with the mapping writes of its finalize.
+    ///
+    /// The writes leave out the fee, except that a `credits.aleo` balance the call writes
+    /// and the fee also pays from shows the value after both.
+    pub fn execute_with_receipt(
+        &self,
+        account: &Account<TestnetV0>,
+        program_id: &ProgramID<TestnetV0>,
+        function_name: &Identifier<TestnetV0>,
+        inputs: Vec<Value<TestnetV0>>,
+        dependencies: &[ProgramID<TestnetV0>],
+    ) -> Result<Receipt<TestnetV0>> {
+        log::info!("Creating local tx: {program_id}.{function_name}");
+
+        self.ensure_program_loaded(program_id, dependencies)?;
+
+        let (transaction, outputs) =
+            self.execution_transaction(account, program_id, function_name, inputs)?;
+
+        self.pull_finalize_state(&transaction)?;
+        self.record_stats(&transaction)?;
+        let writes = self.commit_with_writes(&transaction)?;
+
+        let receipt = Receipt {
+            transaction_id: transaction.id(),
+            outputs: caller_outputs(outputs, &transaction, &account.address())?,
+            writes,
+        };
+        *self.last_receipt.lock().unwrap() = Some(receipt.clone());
+        Ok(receipt)
+    }
+
+    /// The receipt of the last call executed by this VM or its clones.
+    pub fn last_receipt(&self) -> Option<Receipt<TestnetV0>> {
+        self.last_receipt.lock().unwrap().clone()
+    }
+
+    /// Execute a call and finalize it speculatively on the current state, without committing it.
+    ///
+    /// Finalize runs in an atomic batch of the live mapping store that is aborted afterwards,
+    /// so nothing is copied and nothing is kept.
+    /// The writes leave out the fee, except that a `credits.aleo` balance the call writes
+    /// and the fee also pays from shows the value after both.
+    pub fn simulate(
+        &self,
+        account: &Account<TestnetV0>,
+        program_id: &ProgramID<TestnetV0>,
+        function_name: &Identifier<TestnetV0>,
+        inputs: Vec<Value<TestnetV0>>,
+        dependencies: &[ProgramID<TestnetV0>],
+    ) -> Result<Simulation<TestnetV0>> {
+        log::info!("Simulating local tx: {program_id}.{function_name}");
+
+        self.ensure_program_loaded(program_id, dependencies)?;
+
+        let (transaction, outputs) =
+            self.execution_transaction(account, program_id, function_name, inputs)?;
+        self.pull_finalize_state(&transaction)?;
+
+        let writes = self.speculate_writes(&transaction)?;
+
+        Ok(Simulation {
+            outputs: caller_outputs(outputs, &transaction, &account.address())?,
+            writes,
+        })
+    }
+
+    /// Build a proofless execution, paid with a record if private fees are enabled.
+    ///
+    /// The record is picked by the cost of an authorization of the call, so the call is only executed once.
+    fn execution_transaction(
+        &self,
+        account: &Account<TestnetV0>,
+        program_id: &ProgramID<TestnetV0>,
+        function_name: &Identifier<TestnetV0>,
+        inputs: Vec<Value<TestnetV0>>,
+    ) -> Result<(Transaction<TestnetV0>, Vec<Value<TestnetV0>>)> {
+        let mut rng = rand::rng();
+
+        let fee_record = self.private_fee_record(
+            account,
+            || {
+                let authorization = self
+                    .vm
+                    .authorize(
+                        account.private_key(),
+                        *program_id,
+                        *function_name,
+                        inputs.iter(),
+                        &mut rand::rng(),
+                    )
+                    .map_err(|e| Error::Other(format!("authorize: {e}")))?;
+                let (base_fee, _) = execution_cost_for_authorization(
+                    &self.vm.process().lock(),
+                    &authorization,
+                    CONSENSUS_VERSION,
+                )
+                .map_err(|e| Error::Other(format!("execution_cost_for_authorization: {e}")))?;
+                Ok(base_fee)
+            },
+            &inputs,
+        )?;
+        let (transaction, response) = self
+            .vm
+            .execute_with_response_local_proofless(
+                account.private_key(),
+                (*program_id, *function_name),
+                inputs.into_iter(),
+                fee_record,
+                0,
+                None,
+                &mut rng,
+            )
+            .map_err(|e| Error::Other(format!("execute_with_response_local_proofless: {e}")))?;
+        Ok((transaction, response.outputs().to_vec()))
+    }
+
+    /// Finalize `transaction` in a new block, returning the mapping writes of its execution.
+    ///
+    /// The written entries come from the finalize operations of speculating the block, whose
+    /// key hashes are matched against the keys finalize touches, and their values are read
+    /// before and after the block. The fee's writes are left out.
+    fn commit_with_writes(
+        &self,
+        transaction: &Transaction<TestnetV0>,
+    ) -> Result<Vec<MappingWrite<TestnetV0>>> {
+        let beacon_account = Account::dev_account(0).map_err(|e| Error::Other(e.to_string()))?;
+        let (block, operations) = crate::local_chain::speculate_transaction(
+            &self.vm,
+            beacon_account.private_key(),
+            transaction,
+            &mut rand::rng(),
+        )?;
+
+        let touched = self.touched_keys(transaction)?;
+        let mut written = MappingKeys::new();
+        let mut unresolved = crate::finalize::written_key_ids(&operations);
+        // Removed and updated keys are found before the block, inserted ones after it.
+        self.resolve_keys(&touched, &mut unresolved, false, &mut written)?;
+        let before = self.entries_of(&written, false);
+        self.vm
+            .add_next_block(&block)
+            .map_err(|e| Error::Other(format!("add_next_block: {e}")))?;
+        self.resolve_keys(&touched, &mut unresolved, false, &mut written)?;
+        crate::finalize::log_unresolved(&touched, &unresolved);
+        let after = self.entries_of(&written, false);
+
+        let writes = mapping_writes(&before, &after);
+        // A fork must not pull the remote value over a key finalize wrote or removed.
+        if let Some(fork) = &self.fork {
+            for write in &writes {
+                fork.mark_written(write.program_id, write.mapping, write.key.clone());
+            }
+        }
+        Ok(writes)
+    }
+    /// Finalize `transaction` in an atomic batch and abort it, returning the mapping writes.
+    ///
+    /// The written entries come from the finalize operations like in `commit_with_writes`,
+    /// and the values after finalize are read from the batch before it is aborted.
+    fn speculate_writes(
+        &self,
+        transaction: &Transaction<TestnetV0>,
+    ) -> Result<Vec<MappingWrite<TestnetV0>>> {
+        let execution = transaction
+            .execution()
+            .ok_or_else(|| Error::Other("Expected an execution transaction".to_string()))?;
+        let touched = self.touched_keys(transaction)?;
+
+        let state = crate::local_chain::construct_finalize_global_state(
+            &self.vm,
+            TestnetV0::BLOCK_TIME as i64,
+        );
+        let finalize_store = self.vm.finalize_store();
+        finalize_store.start_atomic();
+        let writes = self
+            .vm
+            .process()
+            .lock()
+            .finalize_execution(
+                state,
+                finalize_store,
+                execution,
+                transaction.fee_transition().as_ref(),
+            )
+            .map_err(|e| Error::Other(format!("finalize_execution: {e}")))
+            .and_then(|operations| {
+                let mut written = MappingKeys::new();
+                let mut unresolved = crate::finalize::written_key_ids(&operations);
+                self.resolve_keys(&touched, &mut unresolved, false, &mut written)?;
+                self.resolve_keys(&touched, &mut unresolved, true, &mut written)?;
+                crate::finalize::log_unresolved(&touched, &unresolved);
+                let before = self.entries_of(&written, false);
+                let after = self.entries_of(&written, true);
+                Ok(mapping_writes(&before, &after))
+            });
+        finalize_store.abort_atomic();
+        writes
+    }
+
+    /// The keys the finalize blocks of `transaction` read or write, with `None` for a mapping
+    /// that finalize accesses with a key it computes.
+    fn touched_keys(
+        &self,
+        transaction: &Transaction<TestnetV0>,
+    ) -> Result<MappingKeys<TestnetV0>> {
+        let transitions = transaction
+            .execution()
+            .into_iter()
+            .flat_map(|execution| execution.transitions());
+        let mut touched = MappingKeys::new();
+        for access in crate::finalize::mapping_accesses(&self.vm, transitions)? {
+            let keys = touched
+                .entry((access.program_id, access.mapping_name))
+                .or_insert_with(|| Some(IndexSet::new()));
+            match (keys.as_mut(), access.key) {
+                (Some(keys), Some(key)) => {
+                    keys.insert(key);
+                }
+                _ => *keys = None,
+            }
+        }
+        Ok(touched)
+    }
+
+    /// Move the written entries whose key hash matches a key in `touched` into `written`.
+    ///
+    /// The keys of a mapping finalize accesses with computed keys are taken from its
+    /// entries, read confirmed or from the atomic batch in progress.
+    fn resolve_keys(
+        &self,
+        touched: &MappingKeys<TestnetV0>,
+        unresolved: &mut Vec<(Field<TestnetV0>, Field<TestnetV0>)>,
+        speculative: bool,
+        written: &mut MappingKeys<TestnetV0>,
+    ) -> Result<()> {
+        let mut keys = std::collections::HashMap::new();
+        for ((program_id, mapping_name), known) in touched {
+            let mapping_id = crate::finalize::mapping_id(program_id, mapping_name)?;
+            if !unresolved.iter().any(|(id, _)| *id == mapping_id) {
+                continue;
+            }
+            let candidates: Vec<Plaintext<TestnetV0>> = match known {
+                Some(known) => known.iter().cloned().collect(),
+                None => {
+                    let mapping = MappingKeys::from([((*program_id, *mapping_name), None)]);
+                    self.entries_of(&mapping, speculative)
+                        .into_values()
+                        .flat_map(|entries| entries.into_keys())
+                        .collect()
+                }
+            };
+            for key in candidates {
+                let key_id = crate::finalize::key_id(program_id, mapping_name, &key)?;
+                keys.insert((mapping_id, key_id), (*program_id, *mapping_name, key));
+            }
+        }
+        unresolved.retain(|ids| match keys.get(ids) {
+            Some((program_id, mapping_name, key)) => {
+                if let Some(keys) = written
+                    .entry((*program_id, *mapping_name))
+                    .or_insert_with(|| Some(IndexSet::new()))
+                {
+                    keys.insert(key.clone());
+                }
+                false
+            }
+            None => true,
+        });
+        Ok(())
+    }
+
+    /// The entries of `targets`, each either some keys or a whole mapping.
+    ///
+    /// `speculative` also reads the writes of the atomic batch in progress.
+    fn entries_of(
+        &self,
+        targets: &MappingKeys<TestnetV0>,
+        speculative: bool,
+    ) -> MappingEntries<TestnetV0> {
+        let finalize_store = self.vm.finalize_store();
+        let mut entries = MappingEntries::new();
+        for ((program_id, mapping_name), keys) in targets {
+            let mapping = match keys {
+                Some(keys) => keys
+                    .iter()
+                    .filter_map(|key| {
+                        let value = if speculative {
+                            finalize_store.get_value_speculative(*program_id, *mapping_name, key)
+                        } else {
+                            finalize_store.get_value_confirmed(*program_id, *mapping_name, key)
+                        };
+                        Some((key.clone(), value.ok()??))
+                    })
+                    .collect(),
+                None => {
+                    let mapping = if speculative {
+                        finalize_store.get_mapping_speculative(*program_id, *mapping_name)
+                    } else {
+                        finalize_store.get_mapping_confirmed(*program_id, *mapping_name)
+                    };
+                    match mapping {
+                        Ok(mapping) => mapping.into_iter().collect(),
+                        Err(_) => continue,
+                    }
+                }
+            };
+            entries.insert((*program_id, *mapping_name), mapping);
+        }
+        entries
     }
 
-    pub fn vm(&self) -> &VM<TestnetV0, ConsensusMemory<TestnetV0>> {
-        &self.vm
-    }
+    /// Execute a function signed by `account` with the fee paid by `fee_payer`.
+    ///
+    /// The execution and the fee are proven, so this is slower than `execute_and_broadcast`.
+    pub fn execute_sponsored(
+        &self,
+        account: &Account<TestnetV0>,
+        fee_payer: &Account<TestnetV0>,
+        program_id: &ProgramID<TestnetV0>,
+        function_name: &Identifier<TestnetV0>,
+        inputs: Vec<Value<TestnetV0>>,
+        dependencies: &[ProgramID<TestnetV0>],
+    ) -> Result<Vec<CallOutput<TestnetV0>>> {
+        log::info!("Creating sponsored local tx: {program_id}.{function_name}");
 
-    fn contains_program(&self, program_id: &ProgramID<TestnetV0>) -> bool {
-        self.vm.process().contains_program(program_id)
+        let bundle = self.authorize_with_fee_payer(
+            account,
+            fee_payer,
+            program_id,
+            function_name,
+            inputs,
+            dependencies,
+        )?;
+        self.broadcast_authorization(&bundle)
     }
 
-    fn ensure_program_loaded(
+    /// Estimate the cost of a call, including finalize, without committing or broadcasting it.
+    pub fn estimate_execution_fee(
         &self,
+        account: &Account<TestnetV0>,
         program_id: &ProgramID<TestnetV0>,
+        function_name: &Identifier<TestnetV0>,
+        inputs: Vec<Value<TestnetV0>>,
         dependencies: &[ProgramID<TestnetV0>],
-    ) -> Result<()> {
-        if self.contains_program(program_id) {
-            return Ok(());
-        }
-
-        for dep_id in dependencies {
-            if !self.contains_program(dep_id) {
-                return Err(Error::Other(format!(
-                    "LocalVM: dependency '{dep_id}' not on ledger; deploy it first (missing program '{program_id}')"
-                )));
-            }
-        }
+    ) -> Result<ExecutionStats> {
+        self.ensure_program_loaded(program_id, dependencies)?;
 
-        Err(Error::Other(format!(
-            "LocalVM: program '{program_id}' not loaded; deploy via bindings::new first"
-        )))
+        let (transaction, _) = self
+            .vm
+            .execute_with_response_local_proofless(
+                account.private_key(),
+                (*program_id, *function_name),
+                inputs.into_iter(),
+                None,
+                0,
+                None,
+                &mut rand::rng(),
+            )
+            .map_err(|e| Error::Other(format!("execute_with_response_local_proofless: {e}")))?;
+        let execution = transaction
+            .execution()
+            .ok_or_else(|| Error::Other("Expected an execution transaction".to_string()))?;
+        ExecutionStats::new(&self.vm, execution, None, CONSENSUS_VERSION)
+            .map_err(|e| Error::Other(format!("Failed to calculate execution cost: {e}")))
     }
 
-    pub fn deploy_and_broadcast(
+    /// Estimate the cost of deploying `program` without committing or broadcasting it.
+    pub fn estimate_deployment_fee(
         &self,
         deployer: &Account<TestnetV0>,
         program: &Program<TestnetV0>,
         dependencies: &[ProgramID<TestnetV0>],
-    ) -> Result<()> {
-        let program_id = program.id();
-
+    ) -> Result<DeploymentStats> {
         for dep_id in dependencies {
             if !self.contains_program(dep_id) {
                 return Err(Error::Other(format!(
-                    "LocalVM: missing dependency '{dep_id}' before deploying '{program_id}'"
+                    "LocalVM: missing dependency '{dep_id}' before deploying '{}'",
+                    program.id()
                 )));
             }
         }
 
-        log::info!("📦 Deploy: creating proofless deployment tx for '{program_id}'");
-
-        let mut rng = rand::rng();
         let transaction = self
             .vm
-            .deploy_local_proofless(deployer.private_key(), program, None, 0, None, &mut rng)
+            .deploy_local_proofless(
+                deployer.private_key(),
+                program,
+                None,
+                0,
+                None,
+                &mut rand::rng(),
+            )
             .map_err(|e| Error::Other(format!("deploy_local_proofless: {e}")))?;
-
-        let beacon_account = Account::dev_account(0).map_err(|e| Error::Other(e.to_string()))?;
-        let beacon_key = *beacon_account.private_key();
-        crate::local_chain::commit_transaction(&self.vm, &beacon_key, &transaction, &mut rng)?;
-
-        Ok(())
+        let deployment = transaction
+            .deployment()
+            .ok_or_else(|| Error::Other("Expected a deployment transaction".to_string()))?;
+        DeploymentStats::new(
+            &self.vm,
+            &program.id().to_string(),
+            deployment,
+            None,
+            CONSENSUS_VERSION,
+        )
+        .map_err(|e| Error::Other(format!("Failed to calculate deployment cost: {e}")))
     }
 
-    pub fn execute_and_broadcast(
+    /// Sign a call and its fee without executing it.
+    pub fn authorize_call(
         &self,
         account: &Account<TestnetV0>,
         program_id: &ProgramID<TestnetV0>,
         function_name: &Identifier<TestnetV0>,
         inputs: Vec<Value<TestnetV0>>,
         dependencies: &[ProgramID<TestnetV0>],
-    ) -> Result<Vec<Value<TestnetV0>>> {
-        log::info!("Creating local tx: {program_id}.{function_name}");
+    ) -> Result<AuthorizationBundle<TestnetV0>> {
+        self.authorize_with_fee_payer(
+            account,
+            account,
+            program_id,
+            function_name,
+            inputs,
+            dependencies,
+        )
+    }
 
+    fn authorize_with_fee_payer(
+        &self,
+        account: &Account<TestnetV0>,
+        fee_payer: &Account<TestnetV0>,
+        program_id: &ProgramID<TestnetV0>,
+        function_name: &Identifier<TestnetV0>,
+        inputs: Vec<Value<TestnetV0>>,
+        dependencies: &[ProgramID<TestnetV0>],
+    ) -> Result<AuthorizationBundle<TestnetV0>> {
         self.ensure_program_loaded(program_id, dependencies)?;
 
         let mut rng = rand::rng();
-
-        let (transaction, response) = self
+        let authorization = self
             .vm
-            .execute_with_response_local_proofless(
+            .authorize(
                 account.private_key(),
-                (*program_id, *function_name),
-                inputs.into_iter(),
-                None,
+                *program_id,
+                *function_name,
+                inputs.iter(),
+                &mut rng,
+            )
+            .map_err(|e| Error::Other(format!("authorize: {e}")))?;
+        let outputs =
+            NetworkVm::<TestnetV0>::authorization_outputs(&authorization, account.view_key())?;
+
+        let (base_fee, _) = execution_cost_for_authorization(
+            &self.vm.process().lock(),
+            &authorization,
+            CONSENSUS_VERSION,
+        )
+        .map_err(|e| Error::Other(format!("execution_cost_for_authorization: {e}")))?;
+        let execution_id = authorization
+            .to_execution_id()
+            .map_err(|e| Error::Other(format!("to_execution_id: {e}")))?;
+        let fee_authorization = if self.private_fees {
+            let fee_record =
+                select_fee_record(self.unspent_credits(fee_payer)?, base_fee, &inputs)?;
+            self.vm.authorize_fee_private(
+                fee_payer.private_key(),
+                fee_record,
+                base_fee,
                 0,
-                None,
+                execution_id,
                 &mut rng,
             )
-            .map_err(|e| Error::Other(format!("execute_with_response_local_proofless: {e}")))?;
+        } else {
+            self.vm.authorize_fee_public(
+                fee_payer.private_key(),
+                base_fee,
+                0,
+                execution_id,
+                &mut rng,
+            )
+        }
+        .map_err(|e| Error::Other(format!("authorize_fee: {e}")))?;
+
+        Ok(AuthorizationBundle::new(
+            *program_id,
+            *function_name,
+            dependencies.to_vec(),
+            authorization,
+            fee_authorization,
+            outputs,
+        ))
+    }
 
-        let beacon_account = Account::dev_account(0).map_err(|e| Error::Other(e.to_string()))?;
-        let beacon_key = *beacon_account.private_key();
-        crate::local_chain::commit_transaction(&self.vm, &beacon_key, &transaction, &mut rng)?;
+    /// Prove and commit a signed call.
+    pub fn broadcast_authorization(
+        &self,
+        bundle: &AuthorizationBundle<TestnetV0>,
+    ) -> Result<Vec<CallOutput<TestnetV0>>> {
+        self.ensure_program_loaded(bundle.program_id(), bundle.dependencies())?;
+
+        let mut rng = rand::rng();
+        let query = Query::<TestnetV0, BlockMemory<TestnetV0>>::from(self.vm.block_store().clone());
+        let transaction = self
+            .vm
+            .execute_authorization(
+                bundle.authorization().clone(),
+                Some(bundle.fee_authorization().clone()),
+                Some(&query),
+                &mut rng,
+            )
+            .map_err(|e| Error::Other(format!("execute_authorization: {e}")))?;
+
+        self.pull_finalize_state(&transaction)?;
+        self.record_stats(&transaction)?;
+        let writes = self.commit_with_writes(&transaction)?;
 
-        Ok(response.outputs().to_vec())
+        let receipt = Receipt {
+            transaction_id: transaction.id(),
+            outputs: bundle.outputs().to_vec(),
+            writes,
+        };
+        *self.last_receipt.lock().unwrap() = Some(receipt.clone());
+        Ok(receipt.outputs)
     }
 
-    fn block_at_height(&self, height: u32) -> Result<Block<TestnetV0>> {
+    pub(crate) fn block_at_height(&self, height: u32) -> Result<Block<TestnetV0>> {
         let hash = self
             .vm
             .block_store()
@@ -624,7 +2072,12 @@ impl LocalVM {
     pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
         let blocks = crate::local_chain::parse_local_chain_blocks(bytes)?;
         let vm = crate::local_chain::vm_from_local_chain_blocks(&blocks)?;
-        Ok(Self { vm })
+        Ok(Self {
+            vm,
+            private_fees: false,
+            fork: None,
+            last_receipt: Arc::default(),
+        })
     }
 
     pub fn set_mapping_value<N: Network>(
@@ -641,15 +2094,85 @@ impl LocalVM {
             _ => return Err(Error::Other("Mapping key must be plaintext".to_string())),
         };
         let v: Value<TestnetV0> = value.to_string().parse()?;
+        if let Some(fork) = &self.fork {
+            fork.mark_written(program, mapping, k.clone());
+        }
         self.vm
             .finalize_store()
             .update_key_value(program, mapping, k, v)?;
         Ok(())
     }
+
+    /// Remove `key` from a mapping, returning whether it was present.
+    pub fn remove_mapping_value<N: Network>(
+        &self,
+        program_id: &ProgramID<N>,
+        mapping_name: &Identifier<N>,
+        key: &Value<N>,
+    ) -> Result<bool> {
+        let program: ProgramID<TestnetV0> = program_id.to_string().parse()?;
+        let mapping: Identifier<TestnetV0> = mapping_name.to_string().parse()?;
+        let k = match key {
+            Value::Plaintext(p) => p.to_string().parse::<Plaintext<TestnetV0>>()?,
+            _ => return Err(Error::Other("Mapping key must be plaintext".to_string())),
+        };
+        if let Some(fork) = &self.fork {
+            fork.mark_written(program, mapping, k.clone());
+        }
+        let finalize_store = self.vm.finalize_store();
+        if finalize_store
+            .get_value_confirmed(program, mapping, &k)?
+            .is_none()
+        {
+            return Ok(false);
+        }
+        finalize_store.remove_key_value(program, mapping, &k)?;
+        Ok(true)
+    }
+
+    /// All entries of a mapping.
+    ///
+    /// A fork only knows the entries it pulled from the remote or wrote locally, so listing
+    /// a mapping of a fork fails instead of returning a partial list.
+    pub fn mapping_entries<N: Network>(
+        &self,
+        program_id: &ProgramID<N>,
+        mapping_name: &Identifier<N>,
+    ) -> Result<Vec<(Value<N>, Value<N>)>> {
+        if self.fork.is_some() {
+            return Err(Error::Other(format!(
+                "A fork cannot list the entries of {program_id}/{mapping_name}, only look up keys"
+            )));
+        }
+        let program: ProgramID<TestnetV0> = program_id.to_string().parse()?;
+        let mapping: Identifier<TestnetV0> = mapping_name.to_string().parse()?;
+        let entries = match self
+            .vm
+            .finalize_store()
+            .get_mapping_names_confirmed(&program)?
+        {
+            Some(names) if names.contains(&mapping) => self
+                .vm
+                .finalize_store()
+                .get_mapping_confirmed(program, mapping)?,
+            _ => return Ok(Vec::new()),
+        };
+        entries
+            .into_iter()
+            .map(|(key, value)| {
+                Ok((
+                    Value::Plaintext(key.to_string().parse()?),
+                    value.to_string().parse()?,
+                ))
+            })
+            .collect()
+    }
 }
 
 pub struct LocalVMSnapshot {
     bytes: Vec<u8>,
+    private_fees: bool,
+    fork: Option<Fork>,
     finalize_overlay: Vec<(
         ProgramID<TestnetV0>,
         Identifier<TestnetV0>,
@@ -660,20 +2183,32 @@ pub struct LocalVMSnapshot {
 
 impl LocalVMSnapshot {
     pub fn restore(&self) -> LocalVM {
-        let vm = LocalVM::from_bytes(&self.bytes).unwrap();
+        self.try_restore().unwrap()
+    }
+
+    /// Rebuild the snapshotted VM, failing if its chain or mappings cannot be restored.
+    pub fn try_restore(&self) -> Result<LocalVM> {
+        let mut vm = LocalVM::from_bytes(&self.bytes)?;
+        vm.private_fees = self.private_fees;
+        vm.fork = self.fork.as_ref().map(|fork| Arc::new(fork.snapshot()));
         for (program_id, mapping_name, key, value) in &self.finalize_overlay {
             vm.vm
                 .finalize_store()
                 .update_key_value(*program_id, *mapping_name, key.clone(), value.clone())
-                .expect("finalize overlay apply failed");
+                .map_err(|e| Error::Other(format!("Failed to restore mapping value: {e}")))?;
         }
-        vm
+        Ok(vm)
     }
 }
 
 impl LocalVM {
     pub fn snapshot(&self) -> LocalVMSnapshot {
-        let bytes = self.as_bytes().unwrap();
+        self.try_snapshot().unwrap()
+    }
+
+    /// Snapshot the chain and mappings, failing if the chain cannot be serialised.
+    pub fn try_snapshot(&self) -> Result<LocalVMSnapshot> {
+        let bytes = self.as_bytes()?;
         let mut finalize_overlay = Vec::new();
         let process = self.vm.process();
         let finalize_store = self.vm.finalize_store();
@@ -692,10 +2227,12 @@ impl LocalVM {
                 }
             }
         }
-        LocalVMSnapshot {
+        Ok(LocalVMSnapshot {
             bytes,
+            private_fees: self.private_fees,
+            fork: self.fork.as_ref().map(|fork| fork.snapshot()),
             finalize_overlay,
-        }
+        })
     }
 }
 
@@ -767,7 +2304,22 @@ macro_rules! snapshot_store {
 
 impl VMManager<TestnetV0> for LocalVM {
     fn program_exists(&self, program_id: &ProgramID<TestnetV0>) -> Result<bool> {
-        Ok(self.contains_program(program_id))
+        match &self.fork {
+            Some(fork) => self.pull_program(fork, program_id),
+            None => Ok(self.contains_program(program_id)),
+        }
+    }
+
+    fn program(&self, program_id: &ProgramID<TestnetV0>) -> Result<Option<Program<TestnetV0>>> {
+        if !self.program_exists(program_id)? {
+            return Ok(None);
+        }
+        let stack = self
+            .vm
+            .process()
+            .get_stack(*program_id)
+            .map_err(|e| Error::Other(format!("get_stack({program_id}): {e}")))?;
+        Ok(Some(stack.program().clone()))
     }
 
     fn mapping_value(
@@ -784,6 +2336,11 @@ impl VMManager<TestnetV0> for LocalVM {
                 ));
             }
         };
+        if let Some(fork) = &self.fork
+            && self.pull_program(fork, program_id)?
+        {
+            fork.pull_mapping_value(self.vm.finalize_store(), *program_id, *mapping_name, &k)?;
+        }
         self.vm
             .finalize_store()
             .get_value_confirmed(*program_id, *mapping_name, &k)
@@ -796,6 +2353,19 @@ impl VMManager<TestnetV0> for LocalVM {
         view_name: &Identifier<TestnetV0>,
         inputs: Vec<Value<TestnetV0>>,
     ) -> Result<Vec<Value<TestnetV0>>> {
+        if let Some(fork) = &self.fork
+            && self.pull_program(fork, program_id)?
+        {
+            let keys: Vec<&Plaintext<TestnetV0>> = inputs
+                .iter()
+                .filter_map(|input| match input {
+                    Value::Plaintext(plaintext) => Some(plaintext),
+                    _ => None,
+                })
+                .collect();
+            fork.pull_matching_keys(&self.vm, program_id, &keys)?;
+        }
+
         let height = self.vm.block_store().current_block_height();
         let block = self.block_at_height(height)?;
 
@@ -844,7 +2414,7 @@ impl VMManager<TestnetV0> for LocalVM {
         function_name: &Identifier<TestnetV0>,
         inputs: Vec<Value<TestnetV0>>,
         dependencies: &[ProgramID<TestnetV0>],
-    ) -> Result<Vec<Value<TestnetV0>>> {
+    ) -> Result<Vec<CallOutput<TestnetV0>>> {
         LocalVM::execute_and_broadcast(
             self,
             account,
@@ -854,4 +2424,139 @@ impl VMManager<TestnetV0> for LocalVM {
             dependencies,
         )
     }
+
+    fn execute_sponsored(
+        &self,
+        account: &Account<TestnetV0>,
+        fee_payer: &Account<TestnetV0>,
+        program_id: &ProgramID<TestnetV0>,
+        function_name: &Identifier<TestnetV0>,
+        inputs: Vec<Value<TestnetV0>>,
+        dependencies: &[ProgramID<TestnetV0>],
+    ) -> Result<Vec<CallOutput<TestnetV0>>> {
+        LocalVM::execute_sponsored(
+            self,
+            account,
+            fee_payer,
+            program_id,
+            function_name,
+            inputs,
+            dependencies,
+        )
+    }
+
+    fn simulate(
+        &self,
+        account: &Account<TestnetV0>,
+        program_id: &ProgramID<TestnetV0>,
+        function_name: &Identifier<TestnetV0>,
+        inputs: Vec<Value<TestnetV0>>,
+        dependencies: &[ProgramID<TestnetV0>],
+    ) -> Result<Simulation<TestnetV0>> {
+        LocalVM::simulate(
+            self,
+            account,
+            program_id,
+            function_name,
+            inputs,
+            dependencies,
+        )
+    }
+
+    fn estimate_execution_fee(
+        &self,
+        account: &Account<TestnetV0>,
+        program_id: &ProgramID<TestnetV0>,
+        function_name: &Identifier<TestnetV0>,
+        inputs: Vec<Value<TestnetV0>>,
+        dependencies: &[ProgramID<TestnetV0>],
+    ) -> Result<ExecutionStats> {
+        LocalVM::estimate_execution_fee(
+            self,
+            account,
+            program_id,
+            function_name,
+            inputs,
+            dependencies,
+        )
+    }
+
+    fn estimate_deployment_fee(
+        &self,
+        deployer: &Account<TestnetV0>,
+        program: &Program<TestnetV0>,
+        dependencies: &[ProgramID<TestnetV0>],
+    ) -> Result<DeploymentStats> {
+        LocalVM::estimate_deployment_fee(self, deployer, program, dependencies)
+    }
+
+    fn authorize_call(
+        &self,
+        account: &Account<TestnetV0>,
+        program_id: &ProgramID<TestnetV0>,
+        function_name: &Identifier<TestnetV0>,
+        inputs: Vec<Value<TestnetV0>>,
+        dependencies: &[ProgramID<TestnetV0>],
+    ) -> Result<AuthorizationBundle<TestnetV0>> {
+        LocalVM::authorize_call(
+            self,
+            account,
+            program_id,
+            function_name,
+            inputs,
+            dependencies,
+        )
+    }
+
+    fn broadcast_authorization(
+        &self,
+        bundle: &AuthorizationBundle<TestnetV0>,
+    ) -> Result<Vec<CallOutput<TestnetV0>>> {
+        LocalVM::broadcast_authorization(self, bundle)
+    }
+}
+
+impl RecordSource<TestnetV0> for LocalVM {
+    fn unspent_credits(
+        &self,
+        account: &Account<TestnetV0>,
+    ) -> Result<Vec<Record<TestnetV0, Plaintext<TestnetV0>>>> {
+        let block_store = self.vm.block_store();
+        let credits: ProgramID<TestnetV0> = "credits.aleo".parse()?;
+        let mut records = Vec::new();
+        for (commitment, record) in block_store.records() {
+            if !record.is_owner(account.view_key()) {
+                continue;
+            }
+            // Other programs may define records with a `microcredits` field too.
+            let transition_id = block_store
+                .find_transition_id(&commitment)
+                .map_err(|e| Error::Other(format!("Transition lookup failed: {e}")))?;
+            let created_by_credits = block_store
+                .get_transition(&transition_id)
+                .map_err(|e| Error::Other(format!("Transition lookup failed: {e}")))?
+                .is_some_and(|transition| *transition.program_id() == credits);
+            if !created_by_credits {
+                continue;
+            }
+            let serial_number = Record::<TestnetV0, Plaintext<TestnetV0>>::serial_number(
+                *account.private_key(),
+                *commitment,
+            )
+            .map_err(|e| Error::Other(format!("Failed to compute serial number: {e}")))?;
+            if block_store
+                .contains_serial_number(&serial_number)
+                .map_err(|e| Error::Other(format!("Serial number lookup failed: {e}")))?
+            {
+                continue;
+            }
+            let record = record
+                .decrypt(account.view_key())
+                .map_err(|e| Error::Other(format!("Failed to decrypt record: {e}")))?;
+            if microcredits(&record).is_ok() {
+                records.push(record);
+            }
+        }
+        Ok(records)
+    }
 }
@@ -1,5 +1,11 @@
 use leo_bindings_sdk::snarkvm::prelude::*;
-use leo_bindings_sdk::{Account, Client, NetworkVm, ToValue, block_on};
+use leo_bindings_sdk::{
+    Account, AuthorizationBundle, CallOutput, Client, Credentials, Error, GasSnapshot, LocalNode,
+    LocalVM, NetworkVm, RetryLayer, ToValue, VMManager, block_on, microcredits,
+};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::time::{Duration, Instant};
 
 #[test]
 fn test_mapping_query() {
@@ -34,6 +40,47 @@ fn test_transfer_credits() {
     block_on(client.broadcast_wait(&tx)).unwrap();
 }
 
+#[test]
+fn test_offline_authorization() {
+    let account = Account::<TestnetV0>::dev_account(0).unwrap();
+    let client = Client::new("http://127.0.0.1:1", None).unwrap();
+    let vm = NetworkVm::new(&client).unwrap();
+
+    let program = Program::from_str(
+        "program offline_echo.aleo;\n\nfunction echo:\n    input r0 as u64.public;\n    output r0 as u64.public;\n",
+    )
+    .unwrap();
+    let echo_id = ProgramID::from_str("offline_echo.aleo").unwrap();
+    assert!(
+        vm.authorize_call_offline(
+            &account,
+            &echo_id,
+            &Identifier::from_str("echo").unwrap(),
+            vec![7u64.to_value()],
+            &[],
+            None,
+        )
+        .is_err()
+    );
+    vm.add_program_with_edition(&program, 0).unwrap();
+
+    let bundle = vm
+        .authorize_call_offline(
+            &account,
+            &echo_id,
+            &Identifier::from_str("echo").unwrap(),
+            vec![7u64.to_value()],
+            &[],
+            None,
+        )
+        .unwrap();
+    assert_eq!(bundle.outputs()[0].to_string(), "7u64");
+    bundle
+        .to_string()
+        .parse::<AuthorizationBundle<TestnetV0>>()
+        .unwrap();
+}
+
 #[test]
 fn test_delegated_proving() {
     let account = Account::<TestnetV0>::from_env().unwrap();
@@ -61,3 +108,408 @@ fn test_delegated_proving() {
     let tx = block_on(client.prove(&auth)).unwrap();
     block_on(client.broadcast_wait(&tx)).unwrap();
 }
+
+#[test]
+fn test_block_on_inside_runtime() {
+    let node = LocalNode::spawn(LocalVM::new().unwrap()).unwrap();
+    let client = Client::new(&node.endpoint(), None).unwrap();
+    let expected = block_on(client.height::<TestnetV0>()).unwrap();
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let height = runtime.block_on(async { block_on(client.height::<TestnetV0>()) });
+    assert_eq!(height.unwrap(), expected);
+
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let height = runtime
+        .block_on(async {
+            tokio::spawn(async move { block_on(client.height::<TestnetV0>()) }).await
+        })
+        .unwrap();
+    assert_eq!(height.unwrap(), expected);
+}
+
+#[test]
+fn test_block_on_non_send_future() {
+    let node = LocalNode::spawn(LocalVM::new().unwrap()).unwrap();
+    let client = std::rc::Rc::new(Client::new(&node.endpoint(), None).unwrap());
+    let expected = block_on(client.height::<TestnetV0>()).unwrap();
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let height = runtime.block_on(async {
+        let client = client.clone();
+        block_on(async move { client.height::<TestnetV0>().await })
+    });
+    assert_eq!(height.unwrap(), expected);
+}
+
+/// Serve `responses` to one connection each and return the request heads.
+fn serve_responses(responses: Vec<&'static str>) -> (String, std::thread::JoinHandle<Vec<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    let handle = std::thread::spawn(move || {
+        let mut requests = Vec::new();
+        for response in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut head = String::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                head.push_str(&line);
+            }
+            stream.write_all(response.as_bytes()).unwrap();
+            requests.push(head);
+        }
+        requests
+    });
+    (endpoint, handle)
+}
+
+#[test]
+fn test_client_retry_after() {
+    let (endpoint, server) = serve_responses(vec![
+        "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 1\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        "HTTP/1.1 200 OK\r\nContent-Length: 1\r\nConnection: close\r\n\r\n7",
+    ]);
+    let client = Client::builder(&endpoint)
+        .header("X-API-Key", "secret")
+        .build()
+        .unwrap();
+
+    let start = Instant::now();
+    assert_eq!(block_on(client.height::<TestnetV0>()).unwrap(), 7);
+    assert!(start.elapsed() >= Duration::from_secs(1));
+    let requests = server.join().unwrap();
+    assert!(
+        requests
+            .iter()
+            .all(|head| head.to_lowercase().contains("x-api-key: secret"))
+    );
+}
+
+#[test]
+fn test_client_headers_stay_on_endpoints() {
+    let account = Account::<TestnetV0>::dev_account(0).unwrap();
+    let (endpoint, node) = serve_responses(vec![
+        "HTTP/1.1 200 OK\r\nContent-Length: 1\r\nConnection: close\r\n\r\n7",
+    ]);
+    let (jwt_url, jwt) = serve_responses(vec![
+        "HTTP/1.1 200 OK\r\nAuthorization: Bearer token\r\nContent-Length: 18\r\nConnection: close\r\n\r\n{\"exp\":9999999999}",
+    ]);
+    let (prover_url, prover) = serve_responses(vec![
+        "HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+    ]);
+    let client = Client::builder(&endpoint)
+        .header("X-API-Key", "secret")
+        .credentials(Credentials::new("consumer", "key").with_jwt_url(&jwt_url))
+        .build()
+        .unwrap()
+        .with_prover_url(&prover_url);
+
+    assert_eq!(block_on(client.height::<TestnetV0>()).unwrap(), 7);
+    let vm = NetworkVm::new(&client).unwrap();
+    let program = Program::from_str(
+        "program header_echo.aleo;\n\nfunction echo:\n    input r0 as u64.public;\n    output r0 as u64.public;\n",
+    )
+    .unwrap();
+    vm.add_program_with_edition(&program, 0).unwrap();
+    let bundle = vm
+        .authorize_call_offline(
+            &account,
+            &ProgramID::from_str("header_echo.aleo").unwrap(),
+            &Identifier::from_str("echo").unwrap(),
+            vec![7u64.to_value()],
+            &[],
+            None,
+        )
+        .unwrap();
+    assert!(block_on(client.prove(bundle.authorization())).is_err());
+
+    let has_key = |head: &String| head.to_lowercase().contains("x-api-key: secret");
+    assert!(node.join().unwrap().iter().all(has_key));
+    assert!(!jwt.join().unwrap().iter().any(has_key));
+    assert!(!prover.join().unwrap().iter().any(has_key));
+}
+
+#[test]
+fn test_prover_broadcast_failure_is_not_retried() {
+    let account = Account::<TestnetV0>::dev_account(0).unwrap();
+    let node = LocalNode::spawn(LocalVM::new().unwrap()).unwrap();
+    let (jwt_url, _jwt) = serve_responses(vec![
+        "HTTP/1.1 200 OK\r\nAuthorization: Bearer token\r\nContent-Length: 18\r\nConnection: close\r\n\r\n{\"exp\":9999999999}",
+    ]);
+    // The prover drops the connection without answering.
+    let (prover_url, prover) = serve_responses(vec![""]);
+    let client = Client::builder(&node.endpoint())
+        .max_retries(0)
+        .credentials(Credentials::new("consumer", "key").with_jwt_url(&jwt_url))
+        .build()
+        .unwrap()
+        .with_prover_url(&prover_url)
+        .with_prover_broadcast(true);
+    let vm = NetworkVm::new(&client)
+        .unwrap()
+        .layer(RetryLayer::new(3).backoff(Duration::from_millis(10)));
+
+    let err = vm
+        .execute_and_broadcast(
+            &account,
+            &ProgramID::from_str("credits.aleo").unwrap(),
+            &Identifier::from_str("transfer_public").unwrap(),
+            vec![
+                Value::from_str(&account.address().to_string()).unwrap(),
+                Value::from_str("1u64").unwrap(),
+            ],
+            &[],
+        )
+        .unwrap_err();
+    assert!(!err.is_transient(), "{err}");
+    assert_eq!(prover.join().unwrap().len(), 1);
+}
+
+#[test]
+fn test_client_retry_after_too_long() {
+    let (endpoint, server) = serve_responses(vec![
+        "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 120\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+    ]);
+    let client = Client::builder(&endpoint)
+        .max_retry_after(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    match block_on(client.height::<TestnetV0>()) {
+        Err(Error::RateLimited(Some(delay))) => assert_eq!(delay, Duration::from_secs(120)),
+        result => panic!("Expected a rate limit error, got {result:?}"),
+    }
+    server.join().unwrap();
+}
+
+#[test]
+fn test_client_network_error_is_transient() {
+    let dead = {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    };
+    let client = Client::builder(&dead).max_retries(0).build().unwrap();
+    match block_on(client.height::<TestnetV0>()) {
+        Err(e @ Error::Network(_)) => assert!(e.is_transient()),
+        result => panic!("Expected a network error, got {result:?}"),
+    }
+}
+
+/// Forwards part of a credits record, so both outputs are external records.
+const FORWARD_PROGRAM: &str = r"import credits.aleo;
+
+program forward_credits.aleo;
+
+function forward:
+    input r0 as credits.aleo/credits.record;
+    input r1 as address.private;
+    input r2 as u64.private;
+    call credits.aleo/transfer_private r0 r1 r2 into r3 r4;
+    output r3 as credits.aleo/credits.record;
+    output r4 as credits.aleo/credits.record;
+
+constructor:
+    assert.eq edition 0u16;
+";
+
+#[test]
+fn test_external_record_output() {
+    let alice = Account::<TestnetV0>::dev_account(0).unwrap();
+    let bob = Account::<TestnetV0>::new(&mut rand::rng()).unwrap();
+    let credits: ProgramID<TestnetV0> = "credits.aleo".parse().unwrap();
+    let program = Program::<TestnetV0>::from_str(FORWARD_PROGRAM).unwrap();
+    let local = LocalVM::new().unwrap();
+    local
+        .deploy_and_broadcast(&alice, &program, &[credits])
+        .unwrap();
+    let node = LocalNode::spawn(local).unwrap();
+    let client = Client::new(&node.endpoint(), None).unwrap();
+    let vm = NetworkVm::new(&client).unwrap();
+
+    let outputs = vm
+        .execute_and_broadcast(
+            &alice,
+            &credits,
+            &"transfer_public_to_private".parse().unwrap(),
+            vec![alice.address().to_value(), 1_000_000u64.to_value()],
+            &[],
+        )
+        .unwrap();
+    let record = outputs[0].clone().into_value().unwrap();
+
+    // Alice can match her change to its hash, but only sees the hash of Bob's record.
+    let outputs = vm
+        .execute_and_broadcast(
+            &alice,
+            program.id(),
+            &"forward".parse().unwrap(),
+            vec![record, bob.address().to_value(), 400_000u64.to_value()],
+            &[credits],
+        )
+        .unwrap();
+    let CallOutput::External(hash) = &outputs[0] else {
+        panic!("Expected an external record, got {}", outputs[0]);
+    };
+    assert_eq!(outputs[0].to_string(), format!("external record {hash}"));
+    assert!(outputs[0].clone().into_value().is_err());
+    match &outputs[1] {
+        CallOutput::Value(Value::Record(change)) => {
+            assert_eq!(microcredits(change).unwrap(), 600_000)
+        }
+        output => panic!("Expected Alice's change, got {output}"),
+    }
+}
+
+#[test]
+fn test_gas_snapshot_check() {
+    let snapshot: GasSnapshot = "token.aleo 2000000\ntoken.aleo/mint_public 10000\n"
+        .parse()
+        .unwrap();
+    assert_eq!(snapshot.get("token.aleo/mint_public"), Some(10_000));
+    assert_eq!(
+        snapshot.to_string().parse::<GasSnapshot>().unwrap(),
+        snapshot
+    );
+
+    assert!(snapshot.check("token.aleo/mint_public", 9_000, 0.0).is_ok());
+    assert!(
+        snapshot
+            .check("token.aleo/mint_public", 10_400, 5.0)
+            .is_ok()
+    );
+    assert!(snapshot.check("token.aleo/burn", 1, 0.0).is_ok());
+    match snapshot.check("token.aleo/mint_public", 10_600, 5.0) {
+        Err(Error::CostIncreased { snapshot, cost, .. }) => {
+            assert_eq!((snapshot, cost), (10_000, 10_600))
+        }
+        result => panic!("Expected a cost increase, got {result:?}"),
+    }
+    assert!("token.aleo/mint_public ten".parse::<GasSnapshot>().is_err());
+}
+
+#[test]
+fn test_client_failover() {
+    let node = LocalNode::spawn(LocalVM::new().unwrap()).unwrap();
+    let dead = {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    };
+    let client = Client::builder(&dead)
+        .fallback(&node.endpoint())
+        .max_retries(0)
+        .build()
+        .unwrap();
+
+    let key = Account::<TestnetV0>::dev_account(0)
+        .unwrap()
+        .address()
+        .to_value();
+    let served =
+        block_on(client.mapping_served::<TestnetV0>("credits.aleo", "account", &key)).unwrap();
+    assert!(served.value.is_some());
+    assert_eq!(served.endpoints, vec![node.endpoint()]);
+
+    let health = block_on(client.check_health::<TestnetV0>());
+    assert!(!health[0].is_healthy());
+    assert!(health[1].is_healthy());
+}
+
+#[test]
+fn test_client_read_quorum() {
+    let alice = Account::<TestnetV0>::dev_account(0).unwrap();
+    let bob = Account::<TestnetV0>::new(&mut rand::rng()).unwrap();
+    let vm = LocalVM::new().unwrap();
+    vm.execute_and_broadcast(
+        &alice,
+        &ProgramID::from_str("credits.aleo").unwrap(),
+        &Identifier::from_str("transfer_public").unwrap(),
+        vec![bob.address().to_value(), 1u64.to_value()],
+        &[],
+    )
+    .unwrap();
+    let first = LocalNode::spawn(vm.clone()).unwrap();
+    let second = LocalNode::spawn(vm).unwrap();
+    let lagging = LocalNode::spawn(LocalVM::new().unwrap()).unwrap();
+
+    let key = alice.address().to_value();
+    let client = Client::builder(&lagging.endpoint())
+        .fallback(&first.endpoint())
+        .fallback(&second.endpoint())
+        .read_quorum(2)
+        .build()
+        .unwrap();
+    let served =
+        block_on(client.mapping_served::<TestnetV0>("credits.aleo", "account", &key)).unwrap();
+    assert_eq!(served.endpoints.len(), 2);
+    assert!(!served.endpoints.contains(&lagging.endpoint()));
+
+    let client = Client::builder(&lagging.endpoint())
+        .fallback(&first.endpoint())
+        .fallback(&second.endpoint())
+        .read_quorum(3)
+        .build()
+        .unwrap();
+    assert!(block_on(client.mapping::<TestnetV0>("credits.aleo", "account", &key)).is_err());
+}
+
+#[test]
+fn test_typed_endpoints() {
+    let alice = Account::<TestnetV0>::dev_account(0).unwrap();
+    let bob = Account::<TestnetV0>::new(&mut rand::rng()).unwrap();
+    let vm = LocalVM::new().unwrap();
+    vm.execute_and_broadcast(
+        &alice,
+        &ProgramID::from_str("credits.aleo").unwrap(),
+        &Identifier::from_str("transfer_public").unwrap(),
+        vec![bob.address().to_value(), 1u64.to_value()],
+        &[],
+    )
+    .unwrap();
+    let node = LocalNode::spawn(vm.clone()).unwrap();
+    let client = Client::new(&node.endpoint(), None).unwrap();
+
+    let latest = block_on(client.latest_block::<TestnetV0>()).unwrap();
+    assert_eq!(
+        latest.height(),
+        block_on(client.height::<TestnetV0>()).unwrap()
+    );
+    let by_hash = block_on(client.block_by_hash::<TestnetV0>(&latest.hash())).unwrap();
+    assert_eq!(by_hash.height(), latest.height());
+
+    let blocks = block_on(client.blocks::<TestnetV0>(0, latest.height() + 1)).unwrap();
+    assert_eq!(blocks.len() as u32, latest.height() + 1);
+
+    let transactions = block_on(client.block_transactions::<TestnetV0>(latest.height())).unwrap();
+    let confirmed = transactions.iter().next().unwrap();
+    let transaction =
+        block_on(client.transaction::<TestnetV0>(&confirmed.transaction().id())).unwrap();
+    assert_eq!(transaction.id(), confirmed.transaction().id());
+    let unconfirmed_id = confirmed.to_unconfirmed_transaction_id().unwrap();
+    let fetched = block_on(client.confirmed_transaction::<TestnetV0>(&unconfirmed_id)).unwrap();
+    assert!(fetched.is_accepted());
+
+    let names = block_on(client.mapping_names::<TestnetV0>("credits.aleo")).unwrap();
+    assert!(names.contains(&Identifier::from_str("account").unwrap()));
+    assert_eq!(
+        block_on(client.state_root::<TestnetV0>()).unwrap(),
+        vm.vm().block_store().current_state_root()
+    );
+    block_on(client.committee::<TestnetV0>()).unwrap();
+    assert!(matches!(
+        block_on(client.block::<TestnetV0>(latest.height() + 1)),
+        Err(Error::NotFound(_))
+    ));
+}